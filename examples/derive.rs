@@ -44,15 +44,14 @@ fn main() {
     }
 
     println!("\n--- Child-defined schemas ---");
-    if let Some(plugin_schema) = config.get_schema("Plugin") {
-        println!("Schema 'Plugin' fields:");
-        let mut fields: Vec<_> = plugin_schema.fields.iter().collect();
-        fields.sort_by_key(|(k, _)| k.as_str());
-        for (field, ty) in fields {
-            println!("  {field}: {ty}");
+    match config.resolve_schema("Plugin") {
+        Ok(plugin_schema) => {
+            println!("Schema 'Plugin' fields (including inherited):");
+            for field in &plugin_schema.fields {
+                println!("  {}: {}  (from {})", field.name, field.type_name, field.origin);
+            }
         }
-    } else {
-        println!("Schema 'Plugin' not found");
+        Err(e) => println!("Schema 'Plugin' not resolved: {}", e),
     }
 
     println!("\nDone!");
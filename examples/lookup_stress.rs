@@ -0,0 +1,53 @@
+use std::hint::black_box;
+use std::time::Instant;
+use aam_rs::aaml::AAML;
+use aam_rs::builder::AAMBuilder;
+
+fn main() {
+    let count = 1_000_000;
+    let rounds = 5;
+    println!("Lookup benchmark: {} lines, {} rounds", count, rounds);
+
+    let mut builder = AAMBuilder::with_capacity(count * 40);
+    for i in 0..count {
+        builder.add_line(&format!("user_profile_setting_key_{}", i), &format!("value_string_number_{}", i));
+    }
+    let content = builder.build();
+    let aaml = AAML::parse(&content).expect("Parse error");
+
+    let keys: Vec<String> = (0..count).map(|i| format!("user_profile_setting_key_{}", i)).collect();
+
+    let owned_start = Instant::now();
+    for _ in 0..rounds {
+        for key in &keys {
+            black_box(aaml.find_obj(key));
+        }
+    }
+    let owned_duration = owned_start.elapsed();
+
+    let borrowed_start = Instant::now();
+    for _ in 0..rounds {
+        for key in &keys {
+            black_box(aaml.find_obj_ref(key));
+        }
+    }
+    let borrowed_duration = borrowed_start.elapsed();
+
+    let deep_owned_start = Instant::now();
+    for key in &keys {
+        black_box(aaml.find_deep(key));
+    }
+    let deep_owned_duration = deep_owned_start.elapsed();
+
+    let deep_borrowed_start = Instant::now();
+    for key in &keys {
+        black_box(aaml.find_deep_ref(key));
+    }
+    let deep_borrowed_duration = deep_borrowed_start.elapsed();
+
+    let lookups = (count * rounds) as u32;
+    println!("find_obj:      {:?} ({:?}/lookup)", owned_duration, owned_duration / lookups);
+    println!("find_obj_ref:  {:?} ({:?}/lookup)", borrowed_duration, borrowed_duration / lookups);
+    println!("find_deep:     {:?}", deep_owned_duration);
+    println!("find_deep_ref: {:?}", deep_borrowed_duration);
+}
@@ -0,0 +1,43 @@
+use std::fs;
+use std::time::Instant;
+use aam_rs::aaml::AAML;
+use aam_rs::builder::AAMBuilder;
+
+// Compare sequential and parallel parsing (the gain depends on the number of cores):
+//   cargo run --release --example parallel_stress
+//   cargo run --release --example parallel_stress --features parallel
+
+fn generate(lines: usize, prefix: &str) -> String {
+    let mut builder = AAMBuilder::with_capacity(lines * 50);
+    for i in 0..lines {
+        builder.add_line(&format!("{}_setting_key_{}", prefix, i), &format!("\"value # {}\" # trailing comment", i));
+    }
+    builder.build()
+}
+
+fn main() {
+    let cores = std::thread::available_parallelism().map_or(1, |n| n.get());
+    let mode = if cfg!(feature = "parallel") { "parallel" } else { "sequential" };
+    println!("Parse benchmark ({} mode, {} cores)", mode, cores);
+
+    let content = generate(2_000_000, "user_profile");
+    let start = Instant::now();
+    let aaml = AAML::parse(&content).expect("Parse error");
+    println!("Single document: {:?} ({} lines)", start.elapsed(), 2_000_000);
+    drop(aaml);
+
+    let dir = std::env::temp_dir().join("aam_parallel_stress");
+    fs::create_dir_all(&dir).expect("Cannot create temp dir");
+    let mut main = AAMBuilder::new();
+    for i in 0..8 {
+        let path = dir.join(format!("module_{}.aam", i));
+        fs::write(&path, generate(200_000, &format!("module_{}", i))).expect("Cannot write module");
+        main.add_raw(&format!("@import \"{}\"", path.display()));
+    }
+
+    let start = Instant::now();
+    let aaml = AAML::parse(&main.build()).expect("Import error");
+    println!("8 imports:       {:?} (last key: {:?})", start.elapsed(), aaml.find_obj_ref("module_7_setting_key_199999"));
+
+    let _ = fs::remove_dir_all(&dir);
+}
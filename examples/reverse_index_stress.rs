@@ -0,0 +1,68 @@
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::Instant;
+use aam_rs::aaml::AAML;
+use aam_rs::builder::AAMBuilder;
+
+struct CountingAlloc;
+
+static ALLOCATED: AtomicUsize = AtomicUsize::new(0);
+
+unsafe impl GlobalAlloc for CountingAlloc {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        ALLOCATED.fetch_add(layout.size(), Ordering::Relaxed);
+        unsafe { System.alloc(layout) }
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        ALLOCATED.fetch_sub(layout.size(), Ordering::Relaxed);
+        unsafe { System.dealloc(ptr, layout) }
+    }
+}
+
+#[global_allocator]
+static GLOBAL: CountingAlloc = CountingAlloc;
+
+fn mb(bytes: usize) -> f64 {
+    bytes as f64 / 1_048_576.0
+}
+
+fn main() {
+    let count = 100_000;
+    let lookups = 1_000;
+    println!("Reverse lookup benchmark: {} lines, {} lookups", count, lookups);
+
+    let mut builder = AAMBuilder::with_capacity(count * 40);
+    for i in 0..count {
+        builder.add_line(&format!("user_profile_setting_key_{}", i), &format!("value_string_number_{}", i));
+    }
+    let content = builder.build();
+
+    let before = ALLOCATED.load(Ordering::Relaxed);
+    let mut aaml = AAML::parse(&content).expect("Parse error");
+    let plain_bytes = ALLOCATED.load(Ordering::Relaxed) - before;
+
+    let targets: Vec<String> = (0..lookups)
+        .map(|i| format!("value_string_number_{}", i * (count / lookups)))
+        .collect();
+
+    let scan_start = Instant::now();
+    let scan_hits = targets.iter().filter(|t| aaml.find_key(t).is_some()).count();
+    let scan_duration = scan_start.elapsed();
+
+    let before = ALLOCATED.load(Ordering::Relaxed);
+    let build_start = Instant::now();
+    aaml.enable_reverse_index();
+    let build_duration = build_start.elapsed();
+    let index_bytes = ALLOCATED.load(Ordering::Relaxed) - before;
+
+    let index_start = Instant::now();
+    let index_hits = targets.iter().filter(|t| aaml.find_key(t).is_some()).count();
+    let index_duration = index_start.elapsed();
+
+    println!("Map memory:    {:.2} MB", mb(plain_bytes));
+    println!("Index memory:  {:.2} MB (+{:.0}%)", mb(index_bytes), index_bytes as f64 * 100.0 / plain_bytes as f64);
+    println!("Index build:   {:?}", build_duration);
+    println!("Scan lookups:  {:?} ({} hits)", scan_duration, scan_hits);
+    println!("Index lookups: {:?} ({} hits)", index_duration, index_hits);
+}
@@ -0,0 +1,64 @@
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::Instant;
+use aam_rs::aaml::AAML;
+use aam_rs::builder::AAMBuilder;
+
+// Run twice to compare storage backends:
+//   cargo run --release --example storage_stress
+//   cargo run --release --example storage_stress --features arena
+
+struct CountingAlloc;
+
+static ALLOCATED: AtomicUsize = AtomicUsize::new(0);
+static ALLOCATIONS: AtomicUsize = AtomicUsize::new(0);
+
+unsafe impl GlobalAlloc for CountingAlloc {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        ALLOCATED.fetch_add(layout.size(), Ordering::Relaxed);
+        ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+        unsafe { System.alloc(layout) }
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        ALLOCATED.fetch_sub(layout.size(), Ordering::Relaxed);
+        unsafe { System.dealloc(ptr, layout) }
+    }
+}
+
+#[global_allocator]
+static GLOBAL: CountingAlloc = CountingAlloc;
+
+fn main() {
+    let count = 1_000_000;
+    let backend = if cfg!(feature = "arena") { "arena" } else { "boxed" };
+    println!("Storage benchmark ({} backend): {} lines", backend, count);
+
+    let mut builder = AAMBuilder::with_capacity(count * 40);
+    for i in 0..count {
+        let key = format!("user_profile_setting_key_{}", i);
+        // Every tenth line is an alias of the previous key, as in real configs.
+        if i % 10 == 9 {
+            builder.add_line(&key, &format!("user_profile_setting_key_{}", i - 1));
+        } else {
+            builder.add_line(&key, &format!("value_string_number_{}", i));
+        }
+    }
+    let content = builder.build();
+
+    let bytes_before = ALLOCATED.load(Ordering::Relaxed);
+    let allocs_before = ALLOCATIONS.load(Ordering::Relaxed);
+    let parse_start = Instant::now();
+
+    let aaml = AAML::parse(&content).expect("Parse error");
+
+    let parse_duration = parse_start.elapsed();
+    let bytes = ALLOCATED.load(Ordering::Relaxed) - bytes_before;
+    let allocs = ALLOCATIONS.load(Ordering::Relaxed) - allocs_before;
+
+    println!("Source size:  {:.2} MB", content.len() as f64 / 1_048_576.0);
+    println!("Parse time:   {:?}", parse_duration);
+    println!("Live memory:  {:.2} MB", bytes as f64 / 1_048_576.0);
+    println!("Allocations:  {}", allocs);
+    println!("Check:        {:?}", aaml.find_deep_ref("user_profile_setting_key_19"));
+}
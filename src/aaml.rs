@@ -1,56 +1,122 @@
 use crate::commands::{self, Command};
+use crate::condition::{self, ConditionStack, ProfileRank};
+use crate::compiled::{self, Context, Encoder, Lookup, SourceFile, SourceLocation, SpanRecord};
+use crate::deep_result::DeepResult;
 use crate::error::AamlError;
 use crate::found_value::FoundValue;
+use crate::lexer::{self, Line};
+use crate::options::ParseOptions;
+use crate::reader::{AamlReader, Event};
+use crate::snapshot::AamlSnapshot;
+#[cfg(feature = "parallel")]
+use crate::parallel;
+use crate::store::{Entry, Hasher, Store};
+use std::cell::RefCell;
 use std::collections::{HashMap, HashSet};
 use std::fs;
+use std::io::BufRead;
 use std::ops::{Add, AddAssign};
-use std::path::Path;
+use std::path::{Path, PathBuf};
 use std::sync::Arc;
-use crate::commands::schema::SchemaDef;
-use crate::types::Type;
-
-#[cfg(feature = "perf-hash")]
-type Hasher = ahash::RandomState;
-
-#[cfg(not(feature = "perf-hash"))]
-type Hasher = std::collections::hash_map::RandomState;
+use crate::commands::schema::{self, ResolvedField, ResolvedSchema, SchemaDef};
+use crate::commands::typecm;
+use crate::types::{resolve_builtin, SharedType, Type};
+use crate::value::FromAaml;
 
 type AamlString = Box<str>;
 
 pub struct AAML {
-    map: HashMap<AamlString, AamlString, Hasher>,
+    map: Store,
+    next_seq: u64,
     commands: HashMap<String, Arc<dyn Command>>,
-    types: HashMap<String, Box<dyn Type>>,
+    types: HashMap<String, SharedType>,
     schemas: HashMap<String, SchemaDef>,
+    reverse_index: Option<HashMap<AamlString, Vec<AamlString>, Hasher>>,
+    /// Definition text of every type registered through `@type`, replayed by `from_compiled`.
+    type_definitions: HashMap<String, String>,
+    /// Every file read while building this config (root, imports, derived bases).
+    sources: Vec<SourceFile>,
+    /// Glob patterns and `@import?` paths resolved while building this config, so that a
+    /// file appearing later makes a compiled blob stale.
+    lookups: Vec<Lookup>,
+    current_source: Option<usize>,
+    /// Where each key was assigned; only recorded while compiling or for compiled configs.
+    spans: Option<HashMap<AamlString, SpanRecord, Hasher>>,
+    options: ParseOptions,
+    /// Open `@if` / `@profile` blocks of the file currently being merged.
+    conditions: ConditionStack,
+    /// Profiles defined by the parsed files, in order of first appearance.
+    profiles: Vec<String>,
+    /// Rank of the profile that last set each key, while a merge with active profiles is
+    /// in progress.
+    profile_ranks: HashMap<AamlString, ProfileRank, Hasher>,
+    /// Last [`ProfileRank::scope`] handed out; every merged file gets a new one.
+    next_scope: usize,
+    /// Keys demanded by `@require`, checked when the outermost merge completes.
+    required: Vec<String>,
+    /// Prefixes of namespaced imports, longest first. An alias inside one resolves to a key
+    /// of the same namespace first, see [`alias_target`](Self::alias_target).
+    namespaces: Vec<String>,
+    /// Environment variables read by `@if` conditions and the value each had, stored in
+    /// compiled blobs so that a cache built under other values is not reused.
+    env_reads: Vec<(String, Option<String>)>,
+    /// Number of merges in progress; `@require` is checked when it drops back to zero.
+    merge_depth: usize,
+    /// Canonical paths of the files being merged, outermost first, used to detect cycles.
+    file_stack: Vec<PathBuf>,
+    /// Imported files read ahead of time by `apply_lines`, consumed by `merge_file`.
+    #[cfg(feature = "parallel")]
+    prefetched: HashMap<PathBuf, String>,
 }
 
 impl std::fmt::Debug for AAML {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         f.debug_struct("AAML")
-            .field("map", &self.map)
+            .field("map", &MapView(&self.map))
             .field("commands_count", &self.commands.len())
+            .field("reverse_index", &self.reverse_index.is_some())
             .finish()
     }
 }
 
+struct MapView<'a>(&'a Store);
+
+impl std::fmt::Debug for MapView<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_map().entries(self.0.iter().map(|e| (e.key, e.value))).finish()
+    }
+}
+
 impl AAML {
     pub fn new() -> AAML {
-        let mut instance = AAML {
-            map: HashMap::with_hasher(Hasher::new()),
-            commands: HashMap::new(),
-            types: HashMap::new(),
-            schemas: HashMap::new(),
-        };
-        instance.register_default_commands();
-        instance
+        Self::with_capacity(0)
     }
 
     pub fn with_capacity(capacity: usize) -> AAML {
         let mut instance = AAML {
-            map: HashMap::with_capacity_and_hasher(capacity, Hasher::default()),
+            map: Store::with_capacity(capacity),
+            next_seq: 0,
             commands: HashMap::new(),
             types: HashMap::new(),
             schemas: HashMap::new(),
+            reverse_index: None,
+            type_definitions: HashMap::new(),
+            sources: Vec::new(),
+            lookups: Vec::new(),
+            current_source: None,
+            spans: None,
+            options: ParseOptions::default(),
+            conditions: ConditionStack::default(),
+            profiles: Vec::new(),
+            profile_ranks: HashMap::with_hasher(Hasher::default()),
+            next_scope: 0,
+            required: Vec::new(),
+            namespaces: Vec::new(),
+            env_reads: Vec::new(),
+            merge_depth: 0,
+            file_stack: Vec::new(),
+            #[cfg(feature = "parallel")]
+            prefetched: HashMap::new(),
         };
         instance.register_default_commands();
         instance
@@ -64,8 +130,252 @@ impl AAML {
         self.schemas.get(name)
     }
 
-    pub(crate) fn get_map_mut(&mut self) -> &mut HashMap<AamlString, AamlString, Hasher> {
-        &mut self.map
+    /// Flattens a schema with its `extends` parent and `..mixin`s, recursively. A field may
+    /// be redeclared only with the same type or a type with the same base type (e.g. a
+    /// `@type port = i32` overriding `i32`); conflicts, unknown schemas and inheritance
+    /// cycles are reported as `DirectiveError("schema", ..)`.
+    pub fn resolve_schema(&self, name: &str) -> Result<ResolvedSchema, AamlError> {
+        if !self.schemas.contains_key(name) {
+            return Err(AamlError::NotFound(name.to_string()));
+        }
+
+        let mut fields = Vec::new();
+        self.collect_schema_fields(name, &mut Vec::new(), &mut fields)?;
+        Ok(ResolvedSchema { name: name.to_string(), fields })
+    }
+
+    fn collect_schema_fields<'a>(
+        &'a self,
+        name: &'a str,
+        stack: &mut Vec<&'a str>,
+        out: &mut Vec<ResolvedField>,
+    ) -> Result<(), AamlError> {
+        let schema_error = |msg: String| AamlError::DirectiveError("schema".into(), msg);
+
+        if stack.contains(&name) {
+            let chain: Vec<&str> = stack.iter().copied().skip_while(|s| *s != name).chain([name]).collect();
+            return Err(schema_error(format!("Schema inheritance cycle: {}", chain.join(" -> "))));
+        }
+        let schema = self.schemas.get(name).ok_or_else(|| {
+            schema_error(format!("Unknown schema '{}' used by '{}'", name, stack.last().unwrap_or(&name)))
+        })?;
+
+        stack.push(name);
+        for parent in schema.extends.iter().chain(&schema.mixins) {
+            self.collect_schema_fields(parent, stack, out)?;
+        }
+        stack.pop();
+
+        let mut own: Vec<_> = schema.fields.iter().collect();
+        own.sort();
+        for (field, ty) in own {
+            let rules = schema.rules.get(field).cloned().unwrap_or_default();
+            match out.iter_mut().find(|f| f.name == *field) {
+                Some(existing) if existing.type_name == *ty || self.same_base_type(&existing.type_name, ty) => {
+                    existing.type_name = ty.clone();
+                    existing.origin = name.to_string();
+                    existing.rules = rules;
+                }
+                Some(existing) => {
+                    return Err(schema_error(format!(
+                        "Field '{}: {}' in '{}' conflicts with '{}: {}' from '{}'",
+                        field, ty, name, field, existing.type_name, existing.origin
+                    )));
+                }
+                None => out.push(ResolvedField {
+                    name: field.clone(),
+                    type_name: ty.clone(),
+                    origin: name.to_string(),
+                    rules,
+                }),
+            }
+        }
+        Ok(())
+    }
+
+    fn same_base_type(&self, a: &str, b: &str) -> bool {
+        let base = |ty: &str| {
+            self.types
+                .get(ty)
+                .map(|t| t.base_type())
+                .or_else(|| resolve_builtin(ty).ok().map(|t| t.base_type()))
+        };
+        matches!((base(a), base(b)), (Some(x), Some(y)) if x == y)
+    }
+
+    /// Checks the keys described by `schema` (resolved with its parents and mixins): the
+    /// field `port` is read from `prefix.port`, or from `port` when `prefix` is empty.
+    /// Every required field without a default must be present, and present values must
+    /// have the field's type and satisfy its constraint. The first violation is reported as
+    /// [`AamlError::SchemaViolation`]; otherwise the default of every absent field is
+    /// written into the map.
+    pub fn validate_schema(&mut self, schema: &str, prefix: &str) -> Result<(), AamlError> {
+        let resolved = self.resolve_schema(schema)?;
+        for field in &resolved.fields {
+            let key = Self::schema_key(prefix, &field.name);
+            let violation = |details: String| AamlError::SchemaViolation {
+                schema: schema.to_string(),
+                key: key.clone(),
+                details,
+            };
+
+            let Some(value) = self.map.get(&key).map(|e| e.value) else {
+                if field.rules.optional || field.rules.default.is_some() {
+                    continue;
+                }
+                return Err(violation("required field is missing".to_string()));
+            };
+
+            let ty = self.lookup_type(&field.type_name)?;
+            ty.validate(value).map_err(|e| violation(format!("expected {}: {}", field.type_name, e)))?;
+            if let Some(constraint) = &field.rules.constraint {
+                constraint.check_as(value, &*ty).map_err(violation)?;
+            }
+        }
+
+        // Defaults were checked against their field when the schema was defined.
+        for field in &resolved.fields {
+            let key = Self::schema_key(prefix, &field.name);
+            if let Some(default) = &field.rules.default
+                && !self.contains_key(&key)
+            {
+                self.insert_entry(&key, default)?;
+            }
+        }
+        Ok(())
+    }
+
+    fn schema_key(prefix: &str, field: &str) -> String {
+        if prefix.is_empty() {
+            field.to_string()
+        } else {
+            format!("{}.{}", prefix, field)
+        }
+    }
+
+    /// Enables the value → keys index used by reverse lookups.
+    ///
+    /// The index is built from the current map and then kept up to date on every insert,
+    /// turning `find_key`/`find_keys` (and the reverse fallback of `find_obj`) from a full
+    /// scan into a hash lookup, at the cost of one extra copy of every key and distinct value.
+    pub fn enable_reverse_index(&mut self) {
+        if self.reverse_index.is_some() {
+            return;
+        }
+
+        let mut index: HashMap<AamlString, Vec<AamlString>, Hasher> =
+            HashMap::with_capacity_and_hasher(self.map.len(), Hasher::default());
+        for e in self.ordered_entries() {
+            index.entry(Box::from(e.value)).or_default().push(Box::from(e.key));
+        }
+        self.reverse_index = Some(index);
+    }
+
+    /// Drops the reverse index; reverse lookups fall back to scanning the map.
+    pub fn disable_reverse_index(&mut self) {
+        self.reverse_index = None;
+    }
+
+    pub fn has_reverse_index(&self) -> bool {
+        self.reverse_index.is_some()
+    }
+
+    /// Inserts a key-value pair, keeping the reverse index (if enabled) in sync.
+    ///
+    /// Every insert counts as a new definition: the key moves to the end of the
+    /// definition order used by reverse lookups. Fails only when arena storage is full.
+    pub(crate) fn insert_entry(&mut self, key: &str, value: &str) -> Result<(), AamlError> {
+        let seq = self.next_seq;
+        self.next_seq += 1;
+
+        let old = match self.reverse_index {
+            Some(_) => self.map.get(key).map(|e| AamlString::from(e.value)),
+            None => None,
+        };
+        self.map.insert(key, value, seq)?;
+        if let Some(index) = self.reverse_index.as_mut() {
+            if let Some(old) = old {
+                Self::unindex(index, &old, key);
+            }
+            index.entry(Box::from(value)).or_default().push(Box::from(key));
+        }
+        Ok(())
+    }
+
+    /// Removes `key` along with its index, location and profile entries. Returns whether
+    /// the key existed.
+    pub(crate) fn remove_entry(&mut self, key: &str) -> bool {
+        let Some(old) = self.map.get(key) else { return false };
+        if let Some(index) = self.reverse_index.as_mut() {
+            Self::unindex(index, old.value, key);
+        }
+
+        self.map.remove(key);
+        if let Some(spans) = self.spans.as_mut() {
+            spans.remove(key);
+        }
+        self.profile_ranks.remove(key);
+        true
+    }
+
+    /// Merges a `@derive` base loaded with [`child`](Self::child): keys, types and schemas
+    /// are only taken where this config does not define them yet; sources, profiles and
+    /// `@require` lists are combined.
+    pub(crate) fn inherit(&mut self, base: AAML) -> Result<(), AamlError> {
+        self.merge_sources(&base);
+
+        for e in base.ordered_entries() {
+            if !self.contains_key(e.key) {
+                self.insert_entry(e.key, e.value)?;
+            }
+        }
+
+        for (name, schema) in base.schemas {
+            self.schemas.entry(name).or_insert(schema);
+        }
+        for (name, type_def) in base.types {
+            if !self.types.contains_key(&name) {
+                if let Some(definition) = base.type_definitions.get(&name) {
+                    self.type_definitions.insert(name.clone(), definition.clone());
+                }
+                self.types.insert(name, type_def);
+            }
+        }
+
+        for profile in &base.profiles {
+            self.note_profile(profile);
+        }
+        for key in &base.required {
+            self.require_key(key);
+        }
+        Ok(())
+    }
+
+    /// Demands that `key` is defined once loading completes.
+    pub(crate) fn require_key(&mut self, key: &str) {
+        if !self.required.iter().any(|k| k == key) {
+            self.required.push(key.to_string());
+        }
+    }
+
+    pub(crate) fn contains_key(&self, key: &str) -> bool {
+        self.map.get(key).is_some()
+    }
+
+    /// Every key-value pair in definition order.
+    pub(crate) fn ordered_entries(&self) -> Vec<Entry<'_>> {
+        let mut entries: Vec<_> = self.map.iter().collect();
+        entries.sort_unstable_by_key(|e| e.seq);
+        entries
+    }
+
+    fn unindex(index: &mut HashMap<AamlString, Vec<AamlString>, Hasher>, value: &str, key: &str) {
+        if let Some(keys) = index.get_mut(value) {
+            keys.retain(|k| &**k != key);
+            if keys.is_empty() {
+                index.remove(value);
+            }
+        }
     }
 
 
@@ -84,15 +394,36 @@ impl AAML {
         self.commands.insert(command.name().to_string(), Arc::new(command));
     }
 
-    pub fn register_type<T>(&mut self, name: String, type_def: T)
+    /// Registers `type_def` under `name`, replacing any previous type of that name. Fails
+    /// with `InvalidType` and keeps the previous type if an `@type` built on `name` does not
+    /// accept the new one.
+    pub fn register_type<T>(&mut self, name: String, type_def: T) -> Result<(), AamlError>
     where
         T: Type + 'static,
     {
-        self.types.insert(name, Box::new(type_def));
+        self.redefine_types(|aaml| {
+            aaml.type_definitions.remove(&name);
+            aaml.types.insert(name.clone(), Arc::new(type_def));
+            aaml.rebind_type_dependents(&name)
+        })
+    }
+
+    /// A type registered with [`register_type`](Self::register_type) or `@type`.
+    pub fn get_type(&self, name: &str) -> Option<&dyn Type> {
+        self.types.get(name).map(|t| &**t)
+    }
+
+    /// Shared handle to a registered type, kept by `@type` aliases.
+    pub(crate) fn type_handle(&self, name: &str) -> Option<SharedType> {
+        self.types.get(name).cloned()
     }
 
-    pub fn get_type(&self, name: &str) -> Option<&Box<dyn Type>> {
-        self.types.get(name)
+    /// A registered type, or else a built-in one such as `physics::meter`.
+    pub(crate) fn lookup_type(&self, name: &str) -> Result<SharedType, AamlError> {
+        match self.types.get(name) {
+            Some(ty) => Ok(ty.clone()),
+            None => resolve_builtin(name).map(Arc::from),
+        }
     }
 
     pub fn validate_value(&self, type_name: &str, value: &str) -> Result<(), AamlError> {
@@ -106,19 +437,679 @@ impl AAML {
         }
     }
 
-    pub fn merge_content(&mut self, content: &str) -> Result<(), AamlError> {
-        let estimated_size = content.len() / 40;
-        self.map.reserve(estimated_size);
+    /// Merges `other` into this config; its keys and types override existing ones. This is
+    /// `+=` without the panic. Fails with `InvalidType`, before merging anything, if a type
+    /// in `other` breaks an `@type` built on it. With the `arena` feature it fails with
+    /// `InvalidValue` when the merged config outgrows the 4 GiB arena, keeping the entries
+    /// merged so far.
+    pub fn merge(&mut self, mut other: AAML) -> Result<(), AamlError> {
+        let types = std::mem::take(&mut other.types);
+        let type_definitions = std::mem::take(&mut other.type_definitions);
+        self.redefine_types(|aaml| {
+            for name in types.keys() {
+                aaml.type_definitions.remove(name);
+            }
+            aaml.type_definitions.extend(type_definitions);
+            aaml.types.extend(types.iter().map(|(name, ty)| (name.clone(), ty.clone())));
+            types.keys().try_for_each(|name| aaml.rebind_type_dependents(name))
+        })?;
 
-        for (i, line) in content.lines().enumerate() {
-             self.process_line(&line, i + 1)?;
+        self.map.reserve(other.map.len(), 0);
+        for e in other.ordered_entries() {
+            self.insert_entry(e.key, e.value)?;
+            if let Some(spans) = self.spans.as_mut() {
+                spans.remove(e.key);
+            }
+        }
+        self.merge_sources(&other);
+        for profile in &other.profiles {
+            self.note_profile(profile);
         }
         Ok(())
     }
 
+    pub fn merge_content(&mut self, content: &str) -> Result<(), AamlError> {
+        self.scoped_merge(|aaml| aaml.merge_lines(content))
+    }
+
+    /// Runs `merge` with a fresh block stack, so `@if` and `@profile` blocks cannot span
+    /// files, and fails if it leaves a block open. The outermost merge also checks the keys
+    /// demanded by `@require` and forgets which profile set which key.
+    fn scoped_merge(&mut self, merge: impl FnOnce(&mut Self) -> Result<(), AamlError>) -> Result<(), AamlError> {
+        self.next_scope += 1;
+        let nested = self.conditions.nested(self.next_scope);
+        let outer = std::mem::replace(&mut self.conditions, nested);
+        self.merge_depth += 1;
+        let result = merge(self);
+        self.merge_depth -= 1;
+        let inner = std::mem::replace(&mut self.conditions, outer);
+
+        let result = result.and_then(|_| match inner.unclosed() {
+            Some((line, details)) => Err(AamlError::ParseError {
+                line,
+                content: String::new(),
+                details: details.to_string(),
+            }),
+            None => Ok(()),
+        });
+
+        if self.merge_depth > 0 {
+            return result;
+        }
+        self.profile_ranks.clear();
+        let required = std::mem::take(&mut self.required);
+        result?;
+
+        let missing: Vec<String> = required.into_iter().filter(|k| !self.contains_key(k)).collect();
+        if missing.is_empty() {
+            Ok(())
+        } else {
+            Err(AamlError::MissingKeys(missing))
+        }
+    }
+
+    fn merge_lines(&mut self, content: &str) -> Result<(), AamlError> {
+        // Upper bound on the number of assignments: one per line.
+        let line_count = content.bytes().filter(|&b| b == b'\n').count() + 1;
+        self.map.reserve(line_count, content.len());
+
+        #[cfg(feature = "parallel")]
+        {
+            match parallel::lex_chunks(content) {
+                Some(chunks) => self.apply_lines(chunks.into_iter().flatten()),
+                None => self.apply_lines(content.lines().map(lexer::lex_line)),
+            }
+        }
+
+        #[cfg(not(feature = "parallel"))]
+        {
+            for (i, line) in content.lines().enumerate() {
+                 self.apply_line(lexer::lex_line(line), i + 1)?;
+            }
+            Ok(())
+        }
+    }
+
     pub fn merge_file<P: AsRef<Path>>(&mut self, file_path: P) -> Result<(), AamlError> {
-        let content = fs::read_to_string(file_path)?;
-        self.merge_content(&content)
+        let path = file_path.as_ref();
+        let canonical = Self::canonical_path(path);
+        if self.file_stack.contains(&canonical) {
+            return Err(AamlError::DirectiveError(
+                "import".into(),
+                format!("Circular import: {}", self.file_chain(&canonical)),
+            ));
+        }
+        let source = self.add_source(SourceFile::stat(path)?);
+
+        #[cfg(feature = "parallel")]
+        let content = match self.prefetched.remove(path) {
+            Some(content) => content,
+            None => fs::read_to_string(path)?,
+        };
+        #[cfg(not(feature = "parallel"))]
+        let content = fs::read_to_string(path)?;
+
+        let outer = self.current_source.replace(source);
+        self.file_stack.push(canonical);
+        let result = self.merge_content(&content);
+        self.file_stack.pop();
+        self.current_source = outer;
+        result
+    }
+
+    pub(crate) fn canonical_path(path: &Path) -> PathBuf {
+        fs::canonicalize(path).unwrap_or_else(|_| path.to_path_buf())
+    }
+
+    /// Whether `path` (canonical) is currently being merged.
+    pub(crate) fn is_merging(&self, path: &Path) -> bool {
+        self.file_stack.iter().any(|p| p == path)
+    }
+
+    /// `a.aam -> b.aam -> a.aam`: the files being merged from the first occurrence of
+    /// `path`, followed by `path` itself.
+    pub(crate) fn file_chain(&self, path: &Path) -> String {
+        let start = self.file_stack.iter().position(|p| p == path).unwrap_or(0);
+        self.file_stack[start..]
+            .iter()
+            .chain(std::iter::once(&path.to_path_buf()))
+            .map(|p| p.display().to_string())
+            .collect::<Vec<_>>()
+            .join(" -> ")
+    }
+
+    /// Records a file this config was built from, returning its index.
+    fn add_source(&mut self, source: SourceFile) -> usize {
+        match self.sources.iter().position(|s| s.path == source.path) {
+            Some(i) => {
+                self.sources[i] = source;
+                i
+            }
+            None => {
+                self.sources.push(source);
+                self.sources.len() - 1
+            }
+        }
+    }
+
+    /// Records what a glob pattern or optional import path resolved to.
+    pub(crate) fn note_lookup(&mut self, pattern: &str, matches: &[PathBuf]) {
+        let lookup = Lookup { pattern: pattern.to_string(), matches: matches.to_vec() };
+        match self.lookups.iter_mut().find(|l| l.pattern == pattern) {
+            Some(existing) => *existing = lookup,
+            None => self.lookups.push(lookup),
+        }
+    }
+
+    /// Adopts the source list and lookups of a config merged into this one (e.g. a derived
+    /// base), along with the environment variables its conditions read.
+    pub(crate) fn merge_sources(&mut self, other: &AAML) {
+        for source in &other.sources {
+            self.add_source(source.clone());
+        }
+        for lookup in &other.lookups {
+            self.note_lookup(&lookup.pattern, &lookup.matches);
+        }
+        for (name, value) in &other.env_reads {
+            self.note_env_read(name, value.clone());
+        }
+        for namespace in &other.namespaces {
+            self.note_namespace(namespace.clone());
+        }
+    }
+
+    fn note_namespace(&mut self, namespace: String) {
+        if !self.namespaces.contains(&namespace) {
+            let pos = self.namespaces.partition_point(|n| n.len() >= namespace.len());
+            self.namespaces.insert(pos, namespace);
+        }
+    }
+
+    /// The key that `value`, assigned to `key`, refers to as an alias: inside a namespaced
+    /// import `ns.value` if that key exists, otherwise `value` itself.
+    fn alias_target<'a>(&'a self, key: &str, value: &'a str) -> &'a str {
+        self.namespaces
+            .iter()
+            .filter(|ns| key.strip_prefix(ns.as_str()).is_some_and(|rest| rest.starts_with('.')))
+            .find_map(|ns| self.map.get(&format!("{}.{}", ns, value)).map(|e| e.key))
+            .unwrap_or(value)
+    }
+
+    fn note_env_read(&mut self, name: &str, value: Option<String>) {
+        if !self.env_reads.iter().any(|(n, _)| n == name) {
+            self.env_reads.push((name.to_string(), value));
+        }
+    }
+
+    /// Creates an empty config sharing this config's commands, for loading a file whose
+    /// contents are merged selectively.
+    pub(crate) fn child(&self) -> AAML {
+        let mut child = AAML::new();
+        child.commands = self.commands.clone();
+        child.options = self.options.clone();
+        child.conditions = self.conditions.nested(self.next_scope);
+        child.next_scope = self.next_scope;
+        // Loaded as part of this config's merge: `@require` is checked by the parent.
+        child.merge_depth = 1;
+        child.file_stack = self.file_stack.clone();
+        if self.spans.is_some() {
+            child.spans = Some(HashMap::with_hasher(Hasher::default()));
+        }
+        child
+    }
+
+    /// Merges `other` under `namespace`: keys, type names and schema names become
+    /// `namespace.name`, and schema field types and type bases naming one of `other`'s
+    /// types are rewritten to match. Values are kept as written; aliases between imported
+    /// keys resolve within the namespace. With `only`, just the listed keys are merged;
+    /// types and schemas always are.
+    pub(crate) fn merge_namespaced(
+        &mut self,
+        mut other: AAML,
+        namespace: Option<&str>,
+        only: Option<&[String]>,
+    ) -> Result<(), AamlError> {
+        let prefixed = |name: &str| match namespace {
+            Some(ns) => format!("{}.{}", ns, name),
+            None => name.to_string(),
+        };
+        let selected = |key: &str| only.is_none_or(|keys| keys.iter().any(|k| k == key));
+
+        let source_map: Vec<usize> = other.sources.iter().map(|s| self.add_source(s.clone())).collect();
+        for lookup in &other.lookups {
+            self.note_lookup(&lookup.pattern, &lookup.matches);
+        }
+        for (name, value) in &other.env_reads {
+            self.note_env_read(name, value.clone());
+        }
+        for inner in &other.namespaces {
+            self.note_namespace(prefixed(inner));
+        }
+        if let Some(ns) = namespace {
+            self.note_namespace(ns.to_string());
+        }
+
+        for e in other.ordered_entries().into_iter().filter(|e| selected(e.key)) {
+            let key = prefixed(e.key);
+            self.insert_entry(&key, e.value)?;
+            self.profile_ranks.remove(key.as_str());
+
+            if let Some(spans) = self.spans.as_mut() {
+                match other.spans.as_ref().and_then(|s| s.get(e.key)) {
+                    Some(span) => {
+                        let source = span.source.map(|i| source_map[i]);
+                        spans.insert(Box::from(key), SpanRecord { source, line: span.line });
+                    }
+                    None => {
+                        spans.remove(key.as_str());
+                    }
+                }
+            }
+        }
+
+        let schema_names: HashSet<String> = other.schemas.keys().cloned().collect();
+        for (name, mut schema) in other.schemas {
+            for ty in schema.fields.values_mut() {
+                if other.types.contains_key(ty.as_str()) {
+                    *ty = prefixed(ty);
+                }
+            }
+            for parent in schema.extends.iter_mut().chain(schema.mixins.iter_mut()) {
+                if schema_names.contains(parent.as_str()) {
+                    *parent = prefixed(parent);
+                }
+            }
+            self.schemas.insert(prefixed(&name), schema);
+        }
+
+        let type_definitions = std::mem::take(&mut other.type_definitions);
+        self.redefine_types(|aaml| {
+            for (name, type_def) in &other.types {
+                let name = prefixed(name);
+                aaml.type_definitions.remove(&name);
+                aaml.types.insert(name, type_def.clone());
+            }
+            for (name, definition) in type_definitions {
+                let definition = typecm::rename_base(&definition, |base| {
+                    other.types.contains_key(base).then(|| prefixed(base))
+                });
+                aaml.type_definitions.insert(prefixed(&name), definition);
+            }
+            other.types.keys().try_for_each(|name| aaml.rebind_type_dependents(&prefixed(name)))
+        })?;
+        for profile in &other.profiles {
+            self.note_profile(profile);
+        }
+        for key in other.required.iter().filter(|k| selected(k)) {
+            self.require_key(&prefixed(key));
+        }
+        Ok(())
+    }
+
+    /// Registers a type parsed from `@type name = definition`, keeping the definition text
+    /// for compilation, and rebinds the types built on it.
+    pub(crate) fn define_type(
+        &mut self,
+        name: &str,
+        type_def: typecm::TypeDefinition,
+        definition: &str,
+    ) -> Result<(), AamlError> {
+        self.redefine_types(|aaml| {
+            aaml.types.insert(name.to_string(), Arc::new(type_def));
+            aaml.type_definitions.insert(name.to_string(), definition.to_string());
+            aaml.rebind_type_dependents(name)
+        })
+    }
+
+    /// Runs `redefine` and, if it fails, restores every type and `@type` definition as they
+    /// were before, so a rejected redefinition leaves the previous bindings in place.
+    fn redefine_types(
+        &mut self,
+        redefine: impl FnOnce(&mut Self) -> Result<(), AamlError>,
+    ) -> Result<(), AamlError> {
+        let types = self.types.clone();
+        let type_definitions = self.type_definitions.clone();
+        let result = redefine(self);
+        if result.is_err() {
+            self.types = types;
+            self.type_definitions = type_definitions;
+        }
+        result
+    }
+
+    /// Whether following `@type` base names from `base` leads to `name`.
+    pub(crate) fn type_depends_on(&self, base: &str, name: &str) -> bool {
+        let mut current = base;
+        for _ in 0..=self.type_definitions.len() {
+            if current == name {
+                return true;
+            }
+            match self.type_definitions.get(current).and_then(|d| typecm::base_name(d)) {
+                Some(next) => current = next,
+                None => return false,
+            }
+        }
+        false
+    }
+
+    /// Re-parses the `@type` definitions built on `name`, directly or through other
+    /// aliases, so they validate against its current definition just as
+    /// [`from_compiled`](Self::from_compiled) would replay them. Stops at the first
+    /// definition that no longer parses; run it inside [`redefine_types`](Self::redefine_types)
+    /// to keep the previous bindings in that case.
+    fn rebind_type_dependents(&mut self, name: &str) -> Result<(), AamlError> {
+        let mut seen = HashSet::new();
+        let mut stale = vec![name.to_string()];
+        while let Some(target) = stale.pop() {
+            let dependents: Vec<_> = self.type_definitions.iter()
+                .filter(|(_, definition)| typecm::base_name(definition) == Some(target.as_str()))
+                .map(|(dependent, definition)| (dependent.clone(), definition.clone()))
+                .collect();
+            for (dependent, definition) in dependents {
+                if !seen.insert(dependent.clone()) {
+                    continue;
+                }
+                let type_def = typecm::TypeDefinition::parse(self, &definition)
+                    .map_err(|details| AamlError::InvalidType { type_name: dependent.clone(), details })?;
+                self.types.insert(dependent.clone(), Arc::new(type_def));
+                stale.push(dependent);
+            }
+        }
+        Ok(())
+    }
+
+    fn record_span(&mut self, key: &str, line: usize) {
+        if let Some(spans) = self.spans.as_mut() {
+            spans.insert(Box::from(key), SpanRecord { source: self.current_source, line });
+        }
+    }
+
+    /// File and line where `key` was assigned. Only available for configs produced by
+    /// [`compile`](Self::compile) / [`from_compiled`](Self::from_compiled); keys inherited
+    /// through `@derive` have no location.
+    pub fn definition_location(&self, key: &str) -> Option<SourceLocation<'_>> {
+        let span = self.spans.as_ref()?.get(key)?;
+        Some(SourceLocation {
+            file: span.source.and_then(|i| self.sources.get(i)).map(|s| s.path.as_path()),
+            line: span.line,
+        })
+    }
+
+    /// Loads `path` and serializes the result into the versioned binary format, including
+    /// the definition site of every key and the list of source files for staleness checks.
+    pub fn compile<P: AsRef<Path>>(path: P) -> Result<Vec<u8>, AamlError> {
+        Ok(Self::load_tracked(path, ParseOptions::new())?.to_compiled())
+    }
+
+    /// Serializes this config: entries in definition order, `@type` definitions, schemas
+    /// and source files. Types registered programmatically with
+    /// [`register_type`](Self::register_type) are not included.
+    pub fn to_compiled(&self) -> Vec<u8> {
+        let mut enc = Encoder::new();
+
+        enc.count(self.sources.len());
+        for source in &self.sources {
+            enc.source(source);
+        }
+        enc.count(self.lookups.len());
+        for lookup in &self.lookups {
+            enc.lookup(lookup);
+        }
+        enc.context(&self.context());
+
+        let entries = self.ordered_entries();
+        enc.count(entries.len());
+        for e in entries {
+            enc.str(e.key);
+            enc.str(e.value);
+            enc.span(self.spans.as_ref().and_then(|spans| spans.get(e.key)).copied());
+        }
+
+        let mut types: Vec<_> = self.type_definitions.iter().collect();
+        types.sort();
+        enc.count(types.len());
+        for (name, definition) in types {
+            enc.str(name);
+            enc.str(definition);
+        }
+
+        let mut schemas: Vec<_> = self.schemas.iter().collect();
+        schemas.sort_by_key(|(name, _)| *name);
+        enc.count(schemas.len());
+        for (name, schema) in schemas {
+            enc.str(name);
+            enc.str(schema.extends.as_deref().unwrap_or(""));
+            enc.count(schema.mixins.len());
+            for mixin in &schema.mixins {
+                enc.str(mixin);
+            }
+            let mut fields: Vec<_> = schema.fields.iter().collect();
+            fields.sort();
+            enc.count(fields.len());
+            for (field, ty) in fields {
+                let rules = schema.rules.get(field).cloned().unwrap_or_default();
+                enc.str(&if rules.optional { format!("{}?", field) } else { field.clone() });
+                enc.str(&schema::field_declaration(ty, &rules));
+            }
+        }
+
+        enc.count(self.profiles.len());
+        for profile in &self.profiles {
+            enc.str(profile);
+        }
+
+        enc.count(self.namespaces.len());
+        for namespace in &self.namespaces {
+            enc.str(namespace);
+        }
+
+        enc.finish()
+    }
+
+    /// Rebuilds a config from [`compile`](Self::compile) output after verifying its magic,
+    /// version and checksum. Does not check whether the sources changed since; see
+    /// [`is_compiled_fresh`](Self::is_compiled_fresh) and [`load_cached`](Self::load_cached).
+    ///
+    /// Strings are decoded in place, but the config owns its data: each entry is copied
+    /// once into a map presized from the blob (one buffer with the `arena` feature).
+    pub fn from_compiled(bytes: &[u8]) -> Result<Self, AamlError> {
+        let decoded = compiled::decode(bytes)?;
+        let mut aaml = AAML::with_capacity(decoded.entries.len());
+        let bytes = decoded.entries.iter().map(|(key, value, _)| key.len() + value.len()).sum();
+        aaml.map.reserve(0, bytes);
+        aaml.sources = decoded.sources;
+        aaml.lookups = decoded.lookups;
+        aaml.options = decoded.context.vars.iter().fold(ParseOptions::new(), |o, &(k, v)| o.var(k, v));
+        aaml.options = decoded.context.profiles.iter().fold(aaml.options, |o, &p| o.profile(p));
+        aaml.env_reads = decoded.context.env.iter().map(|&(k, v)| (k.to_string(), v.map(String::from))).collect();
+
+        let mut spans = HashMap::with_capacity_and_hasher(decoded.entries.len(), Hasher::default());
+        for (key, value, span) in decoded.entries {
+            aaml.insert_entry(key, value)?;
+            if let Some(span) = span {
+                spans.insert(Box::from(key), span);
+            }
+        }
+        aaml.spans = Some(spans);
+
+        // Definitions are stored sorted by name, so an alias may come before its target:
+        // replay in rounds until every definition has been registered.
+        let mut pending = decoded.types;
+        while !pending.is_empty() {
+            let mut failed = Vec::new();
+            let mut error = None;
+            for (name, definition) in pending.iter().copied() {
+                let args = format!("{} = {}", name, definition);
+                if let Err(e) = aaml.run_directive("type", &args, &args, 0) {
+                    failed.push((name, definition));
+                    error = Some(e);
+                }
+            }
+            if failed.len() == pending.len()
+                && let Some(error) = error
+            {
+                return Err(error);
+            }
+            pending = failed;
+        }
+
+        for schema in decoded.schemas {
+            let mut def = SchemaDef {
+                extends: schema.extends.map(String::from),
+                mixins: schema.mixins.into_iter().map(String::from).collect(),
+                ..SchemaDef::default()
+            };
+            for (field, declaration) in schema.fields {
+                let (field, ty, rules) = schema::parse_field(field, declaration)
+                    .map_err(|e| AamlError::InvalidCompiled(format!("bad schema field '{}': {}", field, e)))?;
+                def.fields.insert(field.to_string(), ty);
+                def.rules.insert(field.to_string(), rules);
+            }
+            aaml.schemas.insert(schema.name.to_string(), def);
+        }
+        aaml.profiles = decoded.profiles.into_iter().map(String::from).collect();
+        for namespace in decoded.namespaces {
+            aaml.note_namespace(namespace.to_string());
+        }
+
+        Ok(aaml)
+    }
+
+    /// `true` if every source file recorded in the blob still has the same size and
+    /// modification time, every glob pattern and `@import?` path still resolves to the same
+    /// files, and every environment variable read by its `@if` conditions still has the
+    /// same value.
+    pub fn is_compiled_fresh(bytes: &[u8]) -> Result<bool, AamlError> {
+        Ok(compiled::decode_header(bytes)?.is_current())
+    }
+
+    /// Loads `source` through the compiled cache at `cache`: the blob is used when it is
+    /// valid, was built from `source` and is fresh; otherwise the text is parsed and the
+    /// cache rewritten (failures to write it are ignored).
+    pub fn load_cached<P: AsRef<Path>, Q: AsRef<Path>>(source: P, cache: Q) -> Result<Self, AamlError> {
+        Self::load_cached_with_options(source, cache, ParseOptions::new())
+    }
+
+    /// Like [`load_cached`](Self::load_cached), with variables and profiles as in
+    /// [`load_with_options`](Self::load_with_options). A blob built under other options is
+    /// treated as stale.
+    pub fn load_cached_with_options<P: AsRef<Path>, Q: AsRef<Path>>(
+        source: P,
+        cache: Q,
+        options: ParseOptions,
+    ) -> Result<Self, AamlError> {
+        let source = source.as_ref();
+        if let Ok(bytes) = fs::read(cache.as_ref()) {
+            let usable = compiled::decode_header(&bytes).is_ok_and(|header| {
+                header.sources.first().is_some_and(|root| root.path == source)
+                    && header.context.vars == options.sorted_vars()
+                    && header.context.profiles.iter().eq(options.profiles())
+                    && header.is_current()
+            });
+            if usable && let Ok(aaml) = Self::from_compiled(&bytes) {
+                return Ok(aaml);
+            }
+        }
+
+        let aaml = Self::load_tracked(source, options)?;
+        let _ = fs::write(cache, aaml.to_compiled());
+        Ok(aaml)
+    }
+
+    fn load_tracked<P: AsRef<Path>>(path: P, options: ParseOptions) -> Result<Self, AamlError> {
+        let mut aaml = AAML::new();
+        aaml.options = options;
+        aaml.spans = Some(HashMap::with_hasher(Hasher::default()));
+        aaml.merge_file(path)?;
+        Ok(aaml)
+    }
+
+    fn context(&self) -> Context<'_> {
+        Context {
+            vars: self.options.sorted_vars(),
+            profiles: self.options.profiles().iter().map(String::as_str).collect(),
+            env: self.env_reads.iter().map(|(name, value)| (name.as_str(), value.as_deref())).collect(),
+        }
+    }
+
+    /// Applies lexed lines in order. Each run of consecutive `@import` directives has its
+    /// files read concurrently before the imports are merged one after another.
+    #[cfg(feature = "parallel")]
+    fn apply_lines<'a>(&mut self, lines: impl Iterator<Item = Line<'a>>) -> Result<(), AamlError> {
+        let mut lines = lines.enumerate().map(|(i, line)| (i + 1, line)).peekable();
+        let mut import_run = Vec::new();
+
+        while let Some((line_num, line)) = lines.next() {
+            if Self::import_path(&line).is_some() {
+                import_run.clear();
+                import_run.push((line_num, line));
+                while let Some((_, next)) = lines.peek() {
+                    match next {
+                        Line::Empty => { lines.next(); }
+                        _ if Self::import_path(next).is_some() => import_run.push(lines.next().unwrap()),
+                        _ => break,
+                    }
+                }
+
+                // The run holds no block boundaries, so it is entirely inside or outside
+                // an inactive `@if`/`@profile` block; skipped imports are not read.
+                let paths: Vec<&str> = import_run.iter().filter_map(|(_, l)| Self::import_path(l)).collect();
+                if paths.len() > 1 && self.conditions.is_active() {
+                    self.prefetched.extend(parallel::read_files(&paths));
+                }
+                let result = import_run.drain(..).try_for_each(|(n, l)| self.apply_line(l, n));
+                // Drop whatever a failed or custom import left unread.
+                for path in paths {
+                    self.prefetched.remove(Path::new(path));
+                }
+                result?;
+                continue;
+            }
+
+            self.apply_line(line, line_num)?;
+        }
+        Ok(())
+    }
+
+    #[cfg(feature = "parallel")]
+    fn import_path<'a>(line: &Line<'a>) -> Option<&'a str> {
+        match line {
+            Line::Directive(body) => match lexer::split_directive(body) {
+                ("import" | "import?", args) => commands::import::plain_import_path(args),
+                _ => None,
+            },
+            _ => None,
+        }
+    }
+
+    /// Merges a document read incrementally from `reader`, without loading it into memory
+    /// first. Directives are executed as they are encountered.
+    pub fn merge_reader<R: BufRead>(&mut self, reader: R) -> Result<(), AamlError> {
+        self.scoped_merge(|aaml| {
+            for event in AamlReader::new(reader) {
+                match event? {
+                    Event::Assignment { key, value, span } => aaml.assign(&key, &value, span.line)?,
+                    Event::Directive { name, args, span } => {
+                        aaml.dispatch_directive(&name, &args, &format!("{} {}", name, args), span.line)?
+                    }
+                    Event::BlockEnd { span } => aaml.close_block(span.line)?,
+                    Event::Comment { .. } => {}
+                }
+            }
+            Ok(())
+        })
+    }
+
+    pub fn from_reader<R: BufRead>(reader: R) -> Result<Self, AamlError> {
+        let mut aaml = AAML::new();
+        aaml.merge_reader(reader)?;
+        Ok(aaml)
+    }
+
+    /// Turns this config into an immutable [`AamlSnapshot`] that can be cloned cheaply
+    /// and shared across threads.
+    pub fn freeze(self) -> AamlSnapshot {
+        AamlSnapshot::from(self)
     }
 
     pub fn parse(content: &str) -> Result<Self, AamlError> {
@@ -128,27 +1119,116 @@ impl AAML {
     }
 
     pub fn load<P: AsRef<Path>>(file_path: P) -> Result<Self, AamlError> {
-        let content = fs::read_to_string(file_path)?;
-        Self::parse(&content)
+        let mut aaml = AAML::new();
+        aaml.merge_file(file_path)?;
+        Ok(aaml)
+    }
+
+    /// Like [`parse`](Self::parse), with variables for `@if` conditions and active profiles.
+    pub fn parse_with_options(content: &str, options: ParseOptions) -> Result<Self, AamlError> {
+        let mut aaml = AAML::new();
+        aaml.options = options;
+        aaml.merge_content(content)?;
+        Ok(aaml)
+    }
+
+    /// Like [`load`](Self::load), with variables for `@if` conditions and active profiles.
+    /// Imported files see the same options.
+    pub fn load_with_options<P: AsRef<Path>>(file_path: P, options: ParseOptions) -> Result<Self, AamlError> {
+        let mut aaml = AAML::new();
+        aaml.options = options;
+        aaml.merge_file(file_path)?;
+        Ok(aaml)
+    }
+
+    /// Loads a file with the given profiles active. `profiles` is a comma-separated list in
+    /// priority order, e.g. `"staging"` or `"local, staging"`.
+    pub fn load_with_profile<P: AsRef<Path>>(file_path: P, profiles: &str) -> Result<Self, AamlError> {
+        let options = profiles
+            .split(',')
+            .map(str::trim)
+            .filter(|p| !p.is_empty())
+            .fold(ParseOptions::new(), ParseOptions::profile);
+        Self::load_with_options(file_path, options)
+    }
+
+    /// Profiles defined by the parsed files and their imports (through `@profile` blocks or
+    /// `key@profile` overrides), in order of first appearance. Files imported only from
+    /// inactive blocks are not read and do not contribute.
+    pub fn list_profiles(&self) -> &[String] {
+        &self.profiles
+    }
+
+    pub fn options(&self) -> &ParseOptions {
+        &self.options
     }
 
     pub fn find_obj(&self, key: &str) -> Option<FoundValue> {
+        self.find_obj_ref(key).map(FoundValue::new)
+    }
+
+    /// Borrowed variant of [`find_obj`](Self::find_obj): no allocation, the result
+    /// points into the map.
+    pub fn find_obj_ref(&self, key: &str) -> Option<&str> {
         self.map.get(key)
-            .map(|v| FoundValue::new(v))
-            .or_else(|| self.find_key(key))
+            .map(|e| e.value)
+            .or_else(|| self.find_key_ref(key))
+    }
+
+    /// Parses the value assigned to `key` as `T`, e.g. `get_as::<u16>("port")` or
+    /// `get_as::<ByteSize>("max_body")`. Unlike `find_obj` there is no reverse lookup;
+    /// a missing key is [`AamlError::NotFound`].
+    pub fn get_as<T: FromAaml>(&self, key: &str) -> Result<T, AamlError> {
+        let value = self.map.get(key).ok_or_else(|| AamlError::NotFound(key.to_string()))?.value;
+        T::from_aaml(value)
+    }
+
+    /// Reads `key` as a quantity of `type_name` (a registered type or a built-in such as
+    /// `physics::meter`), converted to that type's unit: with `@type speed = physics::meterPerSecond`,
+    /// `limit = 36 km/h` reads as `10.0`. Values without a unit are taken as already in it.
+    pub fn get_quantity(&self, key: &str, type_name: &str) -> Result<f64, AamlError> {
+        let value = self.map.get(key).ok_or_else(|| AamlError::NotFound(key.to_string()))?.value;
+        self.lookup_type(type_name)?.quantity(value).map_err(|e| AamlError::InvalidType {
+            type_name: type_name.to_string(),
+            details: e.to_string(),
+        })
+    }
+
+    /// Like [`find_obj`](Self::find_obj), but refuses to guess: a reverse lookup matching
+    /// more than one key fails with [`AamlError::AmbiguousLookup`], and a miss is reported
+    /// as [`AamlError::NotFound`].
+    pub fn find_obj_strict(&self, key: &str) -> Result<FoundValue, AamlError> {
+        if let Some(e) = self.map.get(key) {
+            return Ok(FoundValue::new(e.value));
+        }
+
+        match self.find_keys_ref(key).as_slice() {
+            [] => Err(AamlError::NotFound(key.to_string())),
+            [only] => Ok(FoundValue::new(only)),
+            keys => Err(AamlError::AmbiguousLookup {
+                value: key.to_string(),
+                keys: keys.iter().map(|k| k.to_string()).collect(),
+            }),
+        }
     }
 
     pub fn find_deep(&self, key: &str) -> Option<FoundValue> {
+        self.find_deep_ref(key).map(FoundValue::new)
+    }
+
+    /// Borrowed variant of [`find_deep`](Self::find_deep).
+    pub fn find_deep_ref(&self, key: &str) -> Option<&str> {
         let mut current_key = key;
         let mut last_found = None;
         let mut visited = HashSet::with_hasher(Hasher::default());
 
-        while let Some(next_val) = self.map.get(current_key) {
+        while let Some(next_val) = self.map.get(current_key).map(|e| e.value) {
             if !visited.insert(current_key) {
                 break;
             }
 
-            if visited.contains(&**next_val) {
+            let next_key = self.alias_target(current_key, next_val);
+            if visited.contains(next_key) {
                 if last_found.is_none() {
                     last_found = Some(next_val);
                 }
@@ -156,62 +1236,231 @@ impl AAML {
             }
 
             last_found = Some(next_val);
-            current_key = next_val;
+            current_key = next_key;
         }
 
-        last_found.map(|v| FoundValue::new(v))
+        last_found
+    }
+
+    /// Follows `key` through its aliases like [`find_deep`](Self::find_deep), but reports
+    /// every key visited and whether the chain resolved, looped or never started.
+    pub fn find_deep_trace(&self, key: &str) -> DeepResult {
+        let mut chain: Vec<String> = Vec::new();
+        let mut positions: HashMap<&str, usize, Hasher> = HashMap::with_hasher(Hasher::default());
+        let mut current_key = key;
+
+        loop {
+            let Some(entry) = self.map.get(current_key) else {
+                if chain.is_empty() {
+                    return DeepResult::Dangling { chain: vec![key.to_string()] };
+                }
+                return DeepResult::Resolved { chain, value: FoundValue::new(current_key) };
+            };
+
+            if let Some(&start) = positions.get(current_key) {
+                let cycle = chain[start..].to_vec();
+                return DeepResult::Cycle { chain, cycle };
+            }
+
+            positions.insert(current_key, chain.len());
+            chain.push(current_key.to_string());
+            current_key = self.alias_target(current_key, entry.value);
+        }
     }
 
+    /// Lists every alias cycle in the document (e.g. `loop_a = loop_b`, `loop_b = loop_a`).
+    ///
+    /// Each cycle is reported once, starting from its earliest-defined key.
+    pub fn detect_alias_cycles(&self) -> Vec<Vec<String>> {
+        // 1 = on the path being walked, 2 = fully explored
+        let mut state: HashMap<&str, u8, Hasher> = HashMap::with_hasher(Hasher::default());
+        let mut cycles = Vec::new();
+
+        for start in self.ordered_entries() {
+            let mut path: Vec<&str> = Vec::new();
+            let mut current: &str = start.key;
+
+            loop {
+                match state.get(current) {
+                    Some(1) => {
+                        let from = path.iter().position(|k| *k == current).unwrap_or(0);
+                        let mut cycle = path[from..].to_vec();
+                        let first = (0..cycle.len())
+                            .min_by_key(|&i| self.map.get(cycle[i]).map_or(u64::MAX, |e| e.seq))
+                            .unwrap_or(0);
+                        cycle.rotate_left(first);
+                        cycles.push(cycle.into_iter().map(String::from).collect());
+                        break;
+                    }
+                    Some(_) => break,
+                    None => {}
+                }
+
+                let Some(entry) = self.map.get(current) else { break };
+                state.insert(current, 1);
+                path.push(current);
+                current = self.alias_target(current, entry.value);
+            }
+
+            for k in path {
+                state.insert(k, 2);
+            }
+        }
+
+        cycles
+    }
+
+    /// Returns the earliest-defined key whose value equals `value`.
     pub fn find_key(&self, value: &str) -> Option<FoundValue> {
+        self.find_key_ref(value).map(FoundValue::new)
+    }
+
+    /// Borrowed variant of [`find_key`](Self::find_key).
+    pub fn find_key_ref(&self, value: &str) -> Option<&str> {
+        if let Some(index) = &self.reverse_index {
+            return index.get(value)
+                .and_then(|keys| keys.first())
+                .map(|k| &**k);
+        }
+
         self.map.iter()
-            .find_map(|(k, v)| {
-                if &**v == value {
-                    Some(FoundValue::new(k))
-                } else {
-                    None
-                }
-            })
+            .filter(|e| e.value == value)
+            .min_by_key(|e| e.seq)
+            .map(|e| e.key)
+    }
+
+    /// Returns every key whose value equals `value`, in definition order.
+    pub fn find_keys(&self, value: &str) -> Vec<FoundValue> {
+        self.find_keys_ref(value).into_iter().map(FoundValue::new).collect()
+    }
+
+    /// Borrowed variant of [`find_keys`](Self::find_keys).
+    pub fn find_keys_ref(&self, value: &str) -> Vec<&str> {
+        if let Some(index) = &self.reverse_index {
+            return index.get(value)
+                .map(|keys| keys.iter().map(|k| &**k).collect())
+                .unwrap_or_default();
+        }
+
+        let mut matches: Vec<_> = self.map.iter()
+            .filter(|e| e.value == value)
+            .collect();
+        matches.sort_unstable_by_key(|e| e.seq);
+        matches.into_iter().map(|e| e.key).collect()
     }
 
     pub fn unregister_type(&mut self, name: &str) {
         self.types.remove(name);
+        self.type_definitions.remove(name);
     }
 
     fn register_default_commands(&mut self) {
         self.register_command(commands::import::ImportCommand);
+        self.register_command(commands::import::OptionalImportCommand);
         self.register_command(commands::typecm::TypeCommand);
         self.register_command(commands::schema::SchemaCommand);
         self.register_command(commands::derive::DeriveCommand);
+        self.register_command(commands::unset::UnsetCommand);
+        self.register_command(commands::require::RequireCommand);
     }
 
-    fn process_line(&mut self, raw_line: &str, line_num: usize) -> Result<(), AamlError> {
-        let line = Self::strip_comment(raw_line).trim();
+    fn apply_line(&mut self, line: Line<'_>, line_num: usize) -> Result<(), AamlError> {
+        match line {
+            Line::Empty => Ok(()),
+            Line::Directive(rest) => self.process_directive(rest, line_num),
+            Line::Assignment { key, value } => self.assign(key, value, line_num),
+            Line::BlockEnd => self.close_block(line_num),
+            Line::Invalid { content, details } => Err(AamlError::ParseError {
+                line: line_num,
+                content: content.to_string(),
+                details: details.to_string(),
+            }),
+        }
+    }
 
-        if line.is_empty() {
+    /// Applies an assignment at the current position. `key@profile = value` is an override
+    /// when `profile` is active or was declared by an earlier `@profile` block, and only
+    /// applies when that profile is active; for other suffixes `@` is part of the key.
+    ///
+    /// A value set by an active profile is only replaced by a profile of equal or higher
+    /// priority, or by a plain assignment from another file (e.g. a later import).
+    fn assign(&mut self, key: &str, value: &str, line_num: usize) -> Result<(), AamlError> {
+        if !self.conditions.is_active() {
             return Ok(());
         }
 
-        if let Some(rest) = line.strip_prefix('@') {
-            return self.process_directive(rest, line_num);
-        }
+        let (key, rank) = match key.rsplit_once('@') {
+            Some((base, profile)) if self.is_profile(profile.trim_start()) => {
+                let (base, profile) = (base.trim_end(), profile.trim_start());
+                self.note_profile(profile);
+                match self.options.profile_rank(profile) {
+                    Some(rank) => (base, Some(ProfileRank { rank, scope: self.conditions.scope() })),
+                    None => return Ok(()),
+                }
+            }
+            _ => (key, self.conditions.profile_rank()),
+        };
 
-        match Self::parse_assignment(line) {
-            Ok((key, value)) => {
-                self.map.insert(Box::from(key), Box::from(value));
-                Ok(())
+        let existing = self.profile_ranks.get(key).copied();
+        match (rank, existing) {
+            (None, Some(existing)) if existing.scope == self.conditions.scope() => return Ok(()),
+            (None, Some(_)) => {
+                self.profile_ranks.remove(key);
             }
-            Err(details) => Err(AamlError::ParseError {
+            (Some(rank), Some(existing)) if existing.rank < rank.rank => return Ok(()),
+            (Some(rank), _) => {
+                self.profile_ranks.insert(Box::from(key), rank);
+            }
+            (None, None) => {}
+        }
+
+        self.insert_entry(key, value)?;
+        self.record_span(key, line_num);
+        Ok(())
+    }
+
+    fn is_profile(&self, name: &str) -> bool {
+        self.options.profile_rank(name).is_some() || self.profiles.iter().any(|p| p == name)
+    }
+
+    fn note_profile(&mut self, name: &str) {
+        if !self.profiles.iter().any(|p| p == name) {
+            self.profiles.push(name.to_string());
+        }
+    }
+
+    /// Opens a `@profile name {` block.
+    fn open_profile(&mut self, args: &str, content: &str, line_num: usize) -> Result<(), AamlError> {
+        let name = args.trim().strip_suffix('{').map(str::trim_end).unwrap_or_default();
+        if name.is_empty() || name.contains(char::is_whitespace) {
+            return Err(AamlError::ParseError {
                 line: line_num,
-                content: line.to_string(),
-                details: details.to_string(),
-            }),
+                content: content.to_string(),
+                details: "Expected '@profile <name> {'".to_string(),
+            });
+        }
+
+        if self.conditions.is_active() {
+            self.note_profile(name);
+        }
+        let rank = self.options.profile_rank(name);
+        self.conditions.open_profile(line_num, rank);
+        Ok(())
+    }
+
+    fn close_block(&mut self, line_num: usize) -> Result<(), AamlError> {
+        if !self.conditions.has_open_block() {
+            return self.apply_line(lexer::lex_assignment("}"), line_num);
         }
+        self.conditions.close_profile().map_err(|details| AamlError::ParseError {
+            line: line_num,
+            content: "}".to_string(),
+            details,
+        })
     }
 
     fn process_directive(&mut self, content: &str, line_num: usize) -> Result<(), AamlError> {
-        let mut parts = content.splitn(2, char::is_whitespace);
-        let command_name = parts.next().unwrap_or("").trim();
-        let args = parts.next().unwrap_or("");
+        let (command_name, args) = lexer::split_directive(content);
 
         if command_name.is_empty() {
             return Err(AamlError::ParseError {
@@ -221,6 +1470,45 @@ impl AAML {
             });
         }
 
+        self.dispatch_directive(command_name, args, content, line_num)
+    }
+
+    /// Handles conditional directives and runs commands outside of skipped blocks.
+    fn dispatch_directive(&mut self, command_name: &str, args: &str, content: &str, line_num: usize) -> Result<(), AamlError> {
+        if condition::is_conditional(command_name) {
+            let env_reads = RefCell::new(Vec::new());
+            let lookup = |name: &str| match name.strip_prefix('$') {
+                Some(var) => {
+                    let value = std::env::var(var).ok();
+                    env_reads.borrow_mut().push((var.to_string(), value.clone()));
+                    value
+                }
+                None => self.options.get_var(name).or_else(|| self.map.get(name).map(|e| e.value)).map(str::to_string),
+            };
+            let mut conditions = std::mem::take(&mut self.conditions);
+            let result = conditions.apply(command_name, line_num, || condition::evaluate(args, &lookup));
+            self.conditions = conditions;
+            for (name, value) in env_reads.into_inner() {
+                self.note_env_read(&name, value);
+            }
+
+            return result.map_err(|details| AamlError::ParseError {
+                line: line_num,
+                content: content.to_string(),
+                details,
+            });
+        }
+        if command_name == "profile" {
+            return self.open_profile(args, content, line_num);
+        }
+
+        if !self.conditions.is_active() {
+            return Ok(());
+        }
+        self.run_directive(command_name, args, content, line_num)
+    }
+
+    fn run_directive(&mut self, command_name: &str, args: &str, content: &str, line_num: usize) -> Result<(), AamlError> {
         let command = self.commands.get(command_name).cloned();
 
         if let Some(cmd) = command {
@@ -234,42 +1522,8 @@ impl AAML {
         }
     }
 
-    fn strip_comment(line: &str) -> &str {
-        let mut quote_state = None;
-
-        for (idx, c) in line.char_indices() {
-             match (quote_state, c) {
-                (None, '#') => return &line[..idx],
-                (None, '"' | '\'') => quote_state = Some(c),
-                (Some(q), c) if c == q => quote_state = None,
-                _ => {}
-            }
-        }
-        line
-    }
-
-    fn parse_assignment(line: &'_ str) -> Result<(&'_ str, &'_ str), &'static str> {
-        let (key, val) = line.split_once('=')
-            .ok_or("Missing assignment operator '='")?;
-
-        let key = key.trim();
-        if key.is_empty() {
-            return Err("Key cannot be empty");
-        }
-
-        let value = Self::unwrap_quotes(val);
-        Ok((key, value))
-    }
-
     pub fn unwrap_quotes(s: &str) -> &str {
-        let s = s.trim();
-        if s.starts_with('"') && s.ends_with('"') && s.len() >= 2 {
-            return &s[1..s.len() - 1];
-        }
-        if s.starts_with('\'') && s.ends_with('\'') && s.len() >= 2 {
-            return &s[1..s.len() - 1];
-        }
-        s
+        lexer::unwrap_quotes(s)
     }
 }
 
@@ -277,18 +1531,23 @@ impl Add for AAML {
     type Output = Self;
 
     fn add(mut self, rhs: Self) -> Self {
-        self.map.reserve(rhs.map.len());
-        self.map.extend(rhs.map);
-        self.types.extend(rhs.types);
+        self += rhs;
         self
     }
 }
 
+/// Shorthand for [`AAML::merge`].
+///
+/// # Panics
+///
+/// Panics if a type in `rhs` breaks an `@type` built on it, or, with the `arena` feature,
+/// if the merged config outgrows the 4 GiB arena even after compaction. Use
+/// [`AAML::merge`] where that can happen.
 impl AddAssign for AAML {
     fn add_assign(&mut self, rhs: Self) {
-        self.map.reserve(rhs.map.len());
-        self.map.extend(rhs.map);
-        self.types.extend(rhs.types);
+        if let Err(e) = self.merge(rhs) {
+            panic!("AAML += failed: {}", e);
+        }
     }
 }
 
@@ -1,31 +1,39 @@
 use crate::aaml::AAML;
 use crate::commands::Command;
 use crate::error::AamlError;
+use crate::lexer::split_outside_quotes;
 
+/// `@derive base.aam[, other.aam ...]`: inherits keys, types and schemas the current file
+/// does not define. Bases are parsed with this config's commands and options; with several
+/// bases, earlier ones take precedence over later ones.
 pub struct DeriveCommand;
 
 impl Command for DeriveCommand {
     fn name(&self) -> &str { "derive" }
 
     fn execute(&self, aaml: &mut AAML, args: &str) -> Result<(), AamlError> {
-        let raw_path = args.trim();
-        if raw_path.is_empty() {
+        let paths: Vec<&str> = split_outside_quotes(args, ",")
+            .into_iter()
+            .map(|p| AAML::unwrap_quotes(p.trim()))
+            .collect();
+        if paths.iter().any(|p| p.is_empty()) {
             return Err(AamlError::DirectiveError("derive".into(), "Missing file path".into()));
         }
 
-        let path = AAML::unwrap_quotes(raw_path);
-        let mut base = AAML::load(path)?;
-
-        for (schema_name, schema) in base.get_schemas_mut().drain() {
-            aaml.get_schemas_mut().entry(schema_name).or_insert(schema);
-        }
-
-        for (k, v) in base.get_map_mut().drain() {
-            aaml.get_map_mut().entry(k).or_insert(v);
+        for path in paths {
+            let canonical = AAML::canonical_path(path.as_ref());
+            if aaml.is_merging(&canonical) {
+                return Err(AamlError::DirectiveError(
+                    "derive".into(),
+                    format!("Derive cycle: {}", aaml.file_chain(&canonical)),
+                ));
+            }
+
+            let mut base = aaml.child();
+            base.merge_file(path)?;
+            aaml.inherit(base)?;
         }
 
         Ok(())
     }
 }
-
-
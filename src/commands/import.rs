@@ -1,17 +1,47 @@
+use std::fs;
+use std::path::{Component, Path, PathBuf};
 use crate::aaml::AAML;
 use crate::commands::Command;
 use crate::error::AamlError;
 
+/// `@import path`: merges a file, or every file matching a glob pattern in sorted order.
+/// Fails if the file is missing or the pattern matches nothing.
 pub struct ImportCommand;
 
+/// `@import? path`: like `@import`, but a missing file or an empty match is not an error.
+pub struct OptionalImportCommand;
+
 impl Command for ImportCommand {
     fn name(&self) -> &str {
         "import"
     }
 
     fn execute(&self, aaml: &mut AAML, args: &str) -> Result<(), AamlError> {
-        let raw_path = args.trim();
-        if raw_path.is_empty() {
+        import(aaml, args, false)
+    }
+}
+
+impl Command for OptionalImportCommand {
+    fn name(&self) -> &str {
+        "import?"
+    }
+
+    fn execute(&self, aaml: &mut AAML, args: &str) -> Result<(), AamlError> {
+        import(aaml, args, true)
+    }
+}
+
+/// Parsed `@import` arguments: `path [as namespace] [only { key, ... }]`.
+struct ImportArgs<'a> {
+    path: &'a str,
+    namespace: Option<&'a str>,
+    only: Option<Vec<String>>,
+}
+
+impl<'a> ImportArgs<'a> {
+    fn parse(args: &'a str) -> Result<Self, AamlError> {
+        let trimmed = args.trim();
+        if trimmed.is_empty() {
             return Err(AamlError::ParseError {
                 line: 0,
                 content: args.to_string(),
@@ -19,7 +49,231 @@ impl Command for ImportCommand {
             });
         }
 
-        let path = AAML::unwrap_quotes(raw_path);
-        aaml.merge_file(path)
+        let (path, mut rest) = split_path(trimmed);
+        let mut parsed = ImportArgs { path, namespace: None, only: None };
+
+        loop {
+            rest = rest.trim_start();
+            if rest.is_empty() {
+                return Ok(parsed);
+            }
+
+            if let Some(tail) = strip_keyword(rest, "as") {
+                let tail = tail.trim_start();
+                let end = tail.find(char::is_whitespace).unwrap_or(tail.len());
+                let namespace = &tail[..end];
+                if namespace.is_empty() || namespace.contains(['{', '}', ',']) {
+                    return Err(import_error(format!("Invalid namespace '{}'", namespace)));
+                }
+                parsed.namespace = Some(namespace);
+                rest = &tail[end..];
+            } else if let Some(tail) = strip_keyword(rest, "only") {
+                let body = tail
+                    .trim_start()
+                    .strip_prefix('{')
+                    .ok_or_else(|| import_error("Expected '{' after 'only'".to_string()))?;
+                let (keys, tail) = body
+                    .split_once('}')
+                    .ok_or_else(|| import_error("Expected '}'".to_string()))?;
+                let keys: Vec<String> = keys.split(',').map(str::trim).filter(|k| !k.is_empty()).map(String::from).collect();
+                if keys.is_empty() {
+                    return Err(import_error("'only' needs at least one key".to_string()));
+                }
+                parsed.only = Some(keys);
+                rest = tail;
+            } else {
+                return Err(import_error(format!("Unexpected '{}' after import path", rest)));
+            }
+        }
+    }
+
+    fn is_plain(&self) -> bool {
+        self.namespace.is_none() && self.only.is_none()
+    }
+}
+
+/// Splits the path off the arguments. Quoted paths end at the closing quote; unquoted ones
+/// at the first ` as ` / ` only ` clause.
+fn split_path(args: &str) -> (&str, &str) {
+    if let Some(quote) = args.chars().next().filter(|c| matches!(c, '"' | '\''))
+        && let Some(end) = args[1..].find(quote)
+    {
+        return (&args[1..end + 1], &args[end + 2..]);
+    }
+
+    for (idx, _) in args.match_indices(char::is_whitespace) {
+        let tail = args[idx..].trim_start();
+        if strip_keyword(tail, "as").is_some() || strip_keyword(tail, "only").is_some() {
+            return (args[..idx].trim_end(), tail);
+        }
+    }
+    (AAML::unwrap_quotes(args), "")
+}
+
+fn strip_keyword<'a>(s: &'a str, keyword: &str) -> Option<&'a str> {
+    s.strip_prefix(keyword)
+        .filter(|tail| tail.is_empty() || tail.starts_with(|c: char| c.is_whitespace() || c == '{'))
+}
+
+fn import_error(msg: String) -> AamlError {
+    AamlError::DirectiveError("import".into(), msg)
+}
+
+/// Path of an import that merges a single file as-is, i.e. one whose content can be read
+/// ahead of time.
+#[cfg(feature = "parallel")]
+pub(crate) fn plain_import_path(args: &str) -> Option<&str> {
+    ImportArgs::parse(args)
+        .ok()
+        .filter(|parsed| parsed.is_plain() && !is_pattern(parsed.path))
+        .map(|parsed| parsed.path)
+}
+
+fn import(aaml: &mut AAML, args: &str, optional: bool) -> Result<(), AamlError> {
+    let parsed = ImportArgs::parse(args)?;
+    let path = parsed.path;
+
+    let files = if is_pattern(path) {
+        let matches = expand_glob(path)?;
+        if matches.is_empty() && !optional {
+            return Err(import_error(format!("No files match pattern '{}'", path)));
+        }
+        aaml.note_lookup(path, &matches);
+        matches
+    } else if optional && !Path::new(path).is_file() {
+        aaml.note_lookup(path, &[]);
+        return Ok(());
+    } else {
+        vec![PathBuf::from(path)]
+    };
+
+    if parsed.is_plain() {
+        return files.iter().try_for_each(|file| aaml.merge_file(file));
+    }
+
+    let mut missing: Vec<&str> = parsed.only.iter().flatten().map(String::as_str).collect();
+    for file in &files {
+        let mut child = aaml.child();
+        child.merge_file(file)?;
+        missing.retain(|key| !child.contains_key(key));
+        aaml.merge_namespaced(child, parsed.namespace, parsed.only.as_deref())?;
     }
+
+    // An optional import that matched nothing has no keys to select from.
+    if missing.is_empty() || files.is_empty() {
+        Ok(())
+    } else {
+        Err(import_error(format!("Keys not found in '{}': {}", path, missing.join(", "))))
+    }
+}
+
+/// Returns true if `path` contains glob metacharacters (`*`, `?` or `[`).
+pub(crate) fn is_pattern(path: &str) -> bool {
+    path.contains(['*', '?', '['])
 }
+
+/// Expands a glob pattern into the matching files, sorted by path. Wildcards may appear in
+/// any path segment; they never match `/`, and hidden entries only match a segment that
+/// itself starts with `.`.
+pub(crate) fn expand_glob(pattern: &str) -> Result<Vec<PathBuf>, AamlError> {
+    let components: Vec<Component> = Path::new(pattern).components().collect();
+    let mut candidates = vec![PathBuf::new()];
+
+    for (i, component) in components.iter().enumerate() {
+        let last = i + 1 == components.len();
+        let segment = component.as_os_str().to_string_lossy();
+        let mut next = Vec::new();
+
+        for base in &candidates {
+            if !is_pattern(&segment) {
+                next.push(base.join(component));
+                continue;
+            }
+
+            let dir = if base.as_os_str().is_empty() { Path::new(".") } else { base.as_path() };
+            let Ok(entries) = fs::read_dir(dir) else { continue };
+            for entry in entries {
+                let entry = entry?;
+                let name = entry.file_name().to_string_lossy().into_owned();
+                if name.starts_with('.') && !segment.starts_with('.') {
+                    continue;
+                }
+                if wildcard_match(&segment, &name) {
+                    next.push(base.join(&name));
+                }
+            }
+        }
+
+        next.retain(|p| if last { p.is_file() } else { p.is_dir() });
+        candidates = next;
+    }
+
+    candidates.sort();
+    Ok(candidates)
+}
+
+/// Matches `name` against a single-segment pattern supporting `*`, `?`, `[abc]`, `[a-z]`
+/// and negated classes `[!abc]`.
+pub(crate) fn wildcard_match(pattern: &str, name: &str) -> bool {
+    let pattern: Vec<char> = pattern.chars().collect();
+    let name: Vec<char> = name.chars().collect();
+    let (mut p, mut n) = (0, 0);
+    let mut backtrack = None;
+
+    while n < name.len() {
+        let step = match pattern.get(p) {
+            Some('*') => {
+                backtrack = Some((p, n));
+                p += 1;
+                continue;
+            }
+            Some('?') => Some(1),
+            Some('[') => match_class(&pattern[p..], name[n]),
+            Some(&c) => (c == name[n]).then_some(1),
+            None => None,
+        };
+
+        match (step, backtrack) {
+            (Some(len), _) => {
+                p += len;
+                n += 1;
+            }
+            (None, Some((star, from))) => {
+                p = star + 1;
+                n = from + 1;
+                backtrack = Some((star, from + 1));
+            }
+            (None, None) => return false,
+        }
+    }
+
+    pattern[p..].iter().all(|&c| c == '*')
+}
+
+/// Matches `c` against the character class at the start of `pattern`, returning the class
+/// length on success. An unterminated `[` is treated as a literal.
+fn match_class(pattern: &[char], c: char) -> Option<usize> {
+    let Some(end) = pattern.iter().skip(2).position(|&ch| ch == ']').map(|i| i + 2) else {
+        return (c == '[').then_some(1);
+    };
+
+    let (negated, body) = match pattern[1] {
+        '!' | '^' => (true, &pattern[2..end]),
+        _ => (false, &pattern[1..end]),
+    };
+
+    let mut matched = false;
+    let mut i = 0;
+    while i < body.len() {
+        if i + 2 < body.len() && body[i + 1] == '-' {
+            matched |= (body[i]..=body[i + 2]).contains(&c);
+            i += 3;
+        } else {
+            matched |= body[i] == c;
+            i += 1;
+        }
+    }
+
+    (matched != negated).then_some(end + 1)
+}
+
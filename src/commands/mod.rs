@@ -5,6 +5,8 @@ pub mod import;
 pub mod schema;
 pub mod typecm;
 pub mod derive;
+pub mod unset;
+pub mod require;
 
 pub trait Command: Send + Sync {
     fn name(&self) -> &str;
@@ -0,0 +1,24 @@
+use crate::aaml::AAML;
+use crate::commands::Command;
+use crate::commands::unset::parse_keys;
+use crate::error::AamlError;
+
+/// `@require key1, key2`: fails the load with [`AamlError::MissingKeys`] if any of the keys
+/// is still undefined once every import and derive has been merged.
+pub struct RequireCommand;
+
+impl Command for RequireCommand {
+    fn name(&self) -> &str { "require" }
+
+    fn execute(&self, aaml: &mut AAML, args: &str) -> Result<(), AamlError> {
+        let keys = parse_keys(args);
+        if keys.is_empty() {
+            return Err(AamlError::DirectiveError("require".into(), "Missing key".into()));
+        }
+
+        for key in keys {
+            aaml.require_key(key);
+        }
+        Ok(())
+    }
+}
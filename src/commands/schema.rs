@@ -1,11 +1,305 @@
 use std::collections::HashMap;
+use std::fmt;
+use regex::Regex;
 use crate::aaml::AAML;
 use crate::commands::Command;
 use crate::error::AamlError;
+use crate::types::Type;
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, Default)]
 pub struct SchemaDef {
+    /// Fields declared by this schema itself.
     pub fields: HashMap<String, String>,
+    /// Parent from `@schema Name extends Parent { ... }`.
+    pub extends: Option<String>,
+    /// Schemas mixed in with `..Name` items, in declaration order.
+    pub mixins: Vec<String>,
+    /// Optional marker, default and constraint of each field in `fields`.
+    pub rules: HashMap<String, FieldRules>,
+}
+
+/// Everything about a field besides its type: `name?: type`, `= default` and a constraint.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct FieldRules {
+    pub optional: bool,
+    /// Written into the map by [`AAML::validate_schema`](crate::aaml::AAML::validate_schema)
+    /// when the field is absent.
+    pub default: Option<String>,
+    pub constraint: Option<Constraint>,
+}
+
+#[derive(Clone, Debug)]
+pub enum Constraint {
+    /// `(1..=65535)`, `(0..1)`, `(0..)`: numeric bounds, either of which may be open.
+    Range { min: Option<f64>, max: Option<f64>, inclusive: bool },
+    /// `enum(dev, prod)` in place of the type.
+    OneOf(Vec<String>),
+    /// `/^[a-z]+$/`: the value must match the regular expression.
+    Pattern(Regex),
+}
+
+impl PartialEq for Constraint {
+    fn eq(&self, other: &Self) -> bool {
+        match (self, other) {
+            (Constraint::Pattern(a), Constraint::Pattern(b)) => a.as_str() == b.as_str(),
+            (Constraint::OneOf(a), Constraint::OneOf(b)) => a == b,
+            (
+                Constraint::Range { min, max, inclusive },
+                Constraint::Range { min: min2, max: max2, inclusive: inclusive2 },
+            ) => min == min2 && max == max2 && inclusive == inclusive2,
+            _ => false,
+        }
+    }
+}
+
+impl Constraint {
+    pub fn check(&self, value: &str) -> Result<(), String> {
+        match self {
+            Constraint::Range { .. } => {
+                let n: f64 = value.trim().parse().map_err(|_| format!("'{}' is not a number", value))?;
+                self.check_number(n, value)
+            }
+            Constraint::OneOf(options) if options.iter().any(|o| o == value) => Ok(()),
+            Constraint::OneOf(options) => Err(format!("'{}' is not one of {}", value, options.join(", "))),
+            Constraint::Pattern(re) if re.is_match(value) => Ok(()),
+            Constraint::Pattern(re) => Err(format!("'{}' does not match /{}/", value, re.as_str())),
+        }
+    }
+
+    /// Like [`check`](Self::check), but ranges compare the value as a quantity of `ty`, so
+    /// `where 0..=120` on a speed in km/h also bounds `30 m/s`.
+    pub(crate) fn check_as(&self, value: &str, ty: &dyn Type) -> Result<(), String> {
+        match self {
+            Constraint::Range { .. } => {
+                let n = ty.quantity(value).map_err(|e| e.to_string())?;
+                self.check_number(n, value)
+            }
+            _ => self.check(value),
+        }
+    }
+
+    fn check_number(&self, n: f64, value: &str) -> Result<(), String> {
+        match self {
+            Constraint::Range { min, max, inclusive } => {
+                let above_min = min.is_none_or(|min| n >= min);
+                let below_max = max.is_none_or(|max| if *inclusive { n <= max } else { n < max });
+                if above_min && below_max {
+                    Ok(())
+                } else {
+                    Err(format!("{} is outside {}", value, self))
+                }
+            }
+            _ => self.check(value),
+        }
+    }
+}
+
+impl fmt::Display for Constraint {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Constraint::Range { min, max, inclusive } => {
+                let bound = |b: &Option<f64>| b.map(|b| b.to_string()).unwrap_or_default();
+                write!(f, "({}..{}{})", bound(min), if *inclusive { "=" } else { "" }, bound(max))
+            }
+            Constraint::OneOf(options) => write!(f, "enum({})", options.join(", ")),
+            Constraint::Pattern(re) => write!(f, "/{}/", re.as_str()),
+        }
+    }
+}
+
+/// A schema with its parent and mixins flattened in, see
+/// [`AAML::resolve_schema`](crate::aaml::AAML::resolve_schema).
+#[derive(Clone, Debug)]
+pub struct ResolvedSchema {
+    pub name: String,
+    /// Parent fields first, then each mixin's, then the schema's own; fields declared at the
+    /// same level are sorted by name. An overridden field keeps its original position.
+    pub fields: Vec<ResolvedField>,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct ResolvedField {
+    pub name: String,
+    pub type_name: String,
+    /// Schema that declared the field, or the last one that overrode it.
+    pub origin: String,
+    pub rules: FieldRules,
+}
+
+impl ResolvedSchema {
+    pub fn field(&self, name: &str) -> Option<&ResolvedField> {
+        self.fields.iter().find(|f| f.name == name)
+    }
+}
+
+/// Parses one field: `name` (with an optional trailing `?`) and the declaration after the
+/// colon, `type [constraint] [= default]` or `enum(a, b) [= default]`.
+pub(crate) fn parse_field<'a>(name: &'a str, declaration: &str) -> Result<(&'a str, String, FieldRules), String> {
+    let name = name.trim();
+    let (name, optional) = match name.strip_suffix('?') {
+        Some(name) => (name.trim_end(), true),
+        None => (name, false),
+    };
+    if name.is_empty() {
+        return Err("missing field name".into());
+    }
+
+    let (declaration, default) = match split_outside_groups(declaration, '=') {
+        Some((declaration, default)) => (declaration.trim(), Some(parse_default(default.trim()))),
+        None => (declaration.trim(), None),
+    };
+
+    let (ty, constraint) = if let Some(options) = declaration.strip_prefix("enum(") {
+        let options = options.strip_suffix(')').ok_or("expected ')' after enum options")?;
+        let options: Vec<String> = options.split(',').map(|o| AAML::unwrap_quotes(o.trim()).to_string()).collect();
+        if options.iter().any(String::is_empty) {
+            return Err("empty enum option".into());
+        }
+        ("string".to_string(), Some(Constraint::OneOf(options)))
+    } else {
+        let end = declaration.find(|c: char| c.is_whitespace() || c == '(' || c == '/').unwrap_or(declaration.len());
+        let (ty, rest) = declaration.split_at(end);
+        if ty.is_empty() {
+            return Err("missing type".into());
+        }
+        (ty.to_string(), parse_constraint(rest.trim())?)
+    };
+
+    Ok((name, ty, FieldRules { optional, default, constraint }))
+}
+
+fn parse_constraint(text: &str) -> Result<Option<Constraint>, String> {
+    if text.is_empty() {
+        return Ok(None);
+    }
+
+    if let Some(range) = text.strip_prefix('(').and_then(|t| t.strip_suffix(')')) {
+        return parse_range(range).map(Some);
+    }
+
+    if let Some(pattern) = text.strip_prefix('/').and_then(|t| t.strip_suffix('/')) {
+        return Regex::new(pattern).map(|re| Some(Constraint::Pattern(re))).map_err(|e| e.to_string());
+    }
+
+    Err(format!("unexpected '{}'", text))
+}
+
+/// Parses `1..=10`, `0..1`, `0..` or `..=100` (without the surrounding parentheses).
+pub(crate) fn parse_range(range: &str) -> Result<Constraint, String> {
+    let (min, max, inclusive) = match range.split_once("..=") {
+        Some((min, max)) => (min, max, true),
+        None => {
+            let (min, max) = range.split_once("..").ok_or("expected a range like 1..=10")?;
+            (min, max, false)
+        }
+    };
+    let bound = |b: &str| match b.trim() {
+        "" => Ok(None),
+        b => b.parse::<f64>().map(Some).map_err(|_| format!("bad range bound '{}'", b)),
+    };
+    let (min, max) = (bound(min)?, bound(max)?);
+    if min.is_none() && max.is_none() || inclusive && max.is_none() {
+        return Err("a range needs at least one bound, and '..=' an upper one".into());
+    }
+    Ok(Constraint::Range { min, max, inclusive })
+}
+
+/// A default as written after `=`. Double-quoted defaults may escape `\"` and `\\`, which
+/// is how [`field_declaration`] writes them back.
+fn parse_default(default: &str) -> String {
+    match default.strip_prefix('"').and_then(|d| d.strip_suffix('"')) {
+        Some(inner) => {
+            let mut out = String::with_capacity(inner.len());
+            let mut chars = inner.chars().peekable();
+            while let Some(c) = chars.next() {
+                match chars.peek() {
+                    Some(&next @ ('\\' | '"')) if c == '\\' => {
+                        out.push(next);
+                        chars.next();
+                    }
+                    _ => out.push(c),
+                }
+            }
+            out
+        }
+        _ => AAML::unwrap_quotes(default).to_string(),
+    }
+}
+
+/// Splits a schema body at commas that are not inside `(...)`, `/.../` or quotes.
+fn split_items(body: &str) -> Vec<&str> {
+    let mut items = Vec::new();
+    let mut rest = body;
+    while let Some((item, tail)) = split_outside_groups(rest, ',') {
+        items.push(item);
+        rest = tail;
+    }
+    items.push(rest);
+    items
+}
+
+/// Splits at the first `sep` outside `(...)`, `/.../` and quotes. A `/` only opens a
+/// pattern before the field's `=`, so defaults like `30 km/h` are taken as written.
+fn split_outside_groups(s: &str, sep: char) -> Option<(&str, &str)> {
+    let mut depth = 0usize;
+    let mut in_pattern = false;
+    let mut in_default = false;
+    let mut quote = None;
+    let mut escaped = false;
+
+    for (idx, c) in s.char_indices() {
+        if escaped {
+            escaped = false;
+            continue;
+        }
+        match (quote, c) {
+            (_, '\\') => escaped = true,
+            (Some(q), c) if c == q => quote = None,
+            (Some(_), _) => {}
+            (None, '/') if depth == 0 && !in_default => in_pattern = !in_pattern,
+            _ if in_pattern => {}
+            (None, '"' | '\'') => quote = Some(c),
+            (None, '(') => depth += 1,
+            (None, ')') => depth = depth.saturating_sub(1),
+            (None, c) if c == sep && depth == 0 => return Some((&s[..idx], &s[idx + c.len_utf8()..])),
+            (None, '=') if depth == 0 => in_default = true,
+            _ => {}
+        }
+    }
+    None
+}
+
+/// Every default must be a valid value of its field's type and satisfy the constraint,
+/// compared as a quantity of that type (`30 s` against `physics::second (0..60)`).
+fn check_defaults(aaml: &AAML, schema: &SchemaDef) -> Result<(), AamlError> {
+    let mut fields: Vec<_> = schema.rules.iter().collect();
+    fields.sort_by_key(|(field, _)| *field);
+    for (field, rules) in fields {
+        let Some(default) = &rules.default else { continue };
+        let ty_name = &schema.fields[field];
+        let bad = |details: String| {
+            AamlError::DirectiveError("schema".into(), format!("Bad default for '{}': {}", field, details))
+        };
+        let ty = aaml.lookup_type(ty_name).map_err(|_| bad(format!("unknown type '{}'", ty_name)))?;
+        ty.validate(default).map_err(|e| bad(e.to_string()))?;
+        if let Some(constraint) = &rules.constraint {
+            constraint.check_as(default, &*ty).map_err(bad)?;
+        }
+    }
+    Ok(())
+}
+
+/// Source text of a field declaration after the colon, as accepted by [`parse_field`].
+pub(crate) fn field_declaration(ty: &str, rules: &FieldRules) -> String {
+    let mut out = match &rules.constraint {
+        Some(constraint @ Constraint::OneOf(_)) => constraint.to_string(),
+        Some(constraint) => format!("{} {}", ty, constraint),
+        None => ty.to_string(),
+    };
+    if let Some(default) = &rules.default {
+        out.push_str(&format!(" = \"{}\"", default.replace('\\', "\\\\").replace('"', "\\\"")));
+    }
+    out
 }
 
 pub struct SchemaCommand;
@@ -16,28 +310,42 @@ impl SchemaCommand {
         let (name_part, body_part) = args.split_once('{')
             .ok_or_else(|| AamlError::DirectiveError("schema".into(), "Expected '{'".into()))?;
 
-        let name = name_part.trim();
-        if name.is_empty() {
-            return Err(AamlError::DirectiveError("schema".into(), "Schema name is empty".into()));
-        }
+        let (name, extends) = match name_part.split_whitespace().collect::<Vec<_>>()[..] {
+            [] => return Err(AamlError::DirectiveError("schema".into(), "Schema name is empty".into())),
+            [name] => (name, None),
+            [name, "extends", parent] => (name, Some(parent.to_string())),
+            _ => {
+                return Err(AamlError::DirectiveError(
+                    "schema".into(),
+                    format!("Expected 'Name' or 'Name extends Parent', got '{}'", name_part.trim()),
+                ))
+            }
+        };
 
         let body = body_part.rsplit_once('}')
             .ok_or_else(|| AamlError::DirectiveError("schema".into(), "Expected '}'".into()))?
             .0;
 
-        let mut fields = HashMap::new();
-        for item in body.split(',').map(|s| s.trim()).filter(|s| !s.is_empty()) {
-            let (field, ty) = item.split_once(':')
-                .ok_or_else(|| AamlError::DirectiveError("schema".into(), format!("Bad field: {item}")))?;
-            let field = field.trim();
-            let ty = ty.trim();
-            if field.is_empty() || ty.is_empty() {
-                return Err(AamlError::DirectiveError("schema".into(), format!("Bad field: {item}")));
+        let mut schema = SchemaDef { extends, ..SchemaDef::default() };
+        for item in split_items(body).into_iter().map(str::trim).filter(|s| !s.is_empty()) {
+            if let Some(mixin) = item.strip_prefix("..") {
+                let mixin = mixin.trim();
+                if mixin.is_empty() {
+                    return Err(AamlError::DirectiveError("schema".into(), format!("Bad mixin: {item}")));
+                }
+                schema.mixins.push(mixin.to_string());
+                continue;
             }
-            fields.insert(field.to_string(), ty.to_string());
+
+            let (field, declaration) = item.split_once(':')
+                .ok_or_else(|| AamlError::DirectiveError("schema".into(), format!("Bad field: {item}")))?;
+            let (field, ty, rules) = parse_field(field, declaration)
+                .map_err(|details| AamlError::DirectiveError("schema".into(), format!("Bad field '{item}': {details}")))?;
+            schema.fields.insert(field.to_string(), ty);
+            schema.rules.insert(field.to_string(), rules);
         }
 
-        Ok((name.to_string(), SchemaDef { fields }))
+        Ok((name.to_string(), schema))
     }
 }
 
@@ -46,6 +354,7 @@ impl Command for SchemaCommand {
 
     fn execute(&self, aaml: &mut AAML, args: &str) -> Result<(), AamlError> {
         let (name, schema) = Self::parse(args)?;
+        check_defaults(aaml, &schema)?;
         aaml.get_schemas_mut().insert(name, schema);
 
         Ok(())
@@ -1,12 +1,21 @@
+use regex::Regex;
 use crate::commands::Command;
+use crate::commands::schema::{parse_range, Constraint};
 use crate::error::AamlError;
-use crate::types::{resolve_builtin, Type};
+use crate::types::{resolve_builtin, SharedType, Type};
 use crate::types::primitive_type::PrimitiveType;
 
 pub enum TypeDefinition {
     Primitive(String),
-    Alias(String),
+    /// Another type registered on the same config, by name. Rebound by the config when
+    /// that type is redefined.
+    Alias(String, SharedType),
     Builtin(String),
+    /// `base where 1..=10` or `base matching "re"`: values must be valid for `base` and
+    /// satisfy the constraint.
+    Refined(Box<TypeDefinition>, Constraint),
+    /// `enum { a, b }`: one of the listed strings.
+    Enum(Vec<String>),
 }
 
 impl Type for TypeDefinition {
@@ -25,7 +34,9 @@ impl Type for TypeDefinition {
             TypeDefinition::Primitive(name) => {
                 PrimitiveType::from_name(name).unwrap_or(PrimitiveType::String).base_type()
             }
-            TypeDefinition::Alias(_) => PrimitiveType::String,
+            TypeDefinition::Alias(_, target) => target.base_type(),
+            TypeDefinition::Refined(base, _) => base.base_type(),
+            TypeDefinition::Enum(_) => PrimitiveType::String,
         }
     }
 
@@ -37,11 +48,114 @@ impl Type for TypeDefinition {
             TypeDefinition::Primitive(name) => {
                 PrimitiveType::from_name(name)?.validate(value)
             }
-            TypeDefinition::Alias(_) => Ok(()),
+            TypeDefinition::Alias(_, target) => target.validate(value),
+            TypeDefinition::Refined(base, constraint) => {
+                base.validate(value)?;
+                constraint.check_as(value, base.as_ref()).map_err(AamlError::InvalidValue)
+            }
+            TypeDefinition::Enum(options) => {
+                Constraint::OneOf(options.clone()).check(value).map_err(AamlError::InvalidValue)
+            }
+        }
+    }
+
+    fn quantity(&self, value: &str) -> Result<f64, AamlError> {
+        match self {
+            TypeDefinition::Builtin(path) => resolve_builtin(path)?.quantity(value),
+            TypeDefinition::Primitive(name) => PrimitiveType::from_name(name)?.quantity(value),
+            TypeDefinition::Alias(_, target) => target.quantity(value),
+            TypeDefinition::Refined(base, _) => {
+                self.validate(value)?;
+                base.quantity(value)
+            }
+            TypeDefinition::Enum(_) => {
+                self.validate(value)?;
+                Err(AamlError::InvalidValue(format!("Expected a number, got '{}'", value)))
+            }
+        }
+    }
+}
+
+impl TypeDefinition {
+    /// Parses the right-hand side of `@type name = ...`. Names of types already registered
+    /// on `aaml` take precedence over primitive and builtin names.
+    pub(crate) fn parse(aaml: &crate::aaml::AAML, definition: &str) -> Result<Self, String> {
+        if let Some(body) = strip_enum(definition) {
+            let body = body.trim_start().strip_prefix('{').and_then(|b| b.strip_suffix('}'))
+                .ok_or("expected 'enum { a, b }'")?;
+            let options: Vec<String> = body
+                .split(',')
+                .map(|o| crate::aaml::AAML::unwrap_quotes(o.trim()).to_string())
+                .filter(|o| !o.is_empty())
+                .collect();
+            if options.is_empty() {
+                return Err("enum needs at least one value".into());
+            }
+            return Ok(TypeDefinition::Enum(options));
+        }
+
+        let (base, refinement) = match definition.split_once(char::is_whitespace) {
+            Some((base, rest)) => (base, rest.trim()),
+            None => (definition, ""),
+        };
+        let base = Self::parse_base(aaml, base)?;
+
+        if refinement.is_empty() {
+            return Ok(base);
+        }
+        if let Some(range) = refinement.strip_prefix("where") {
+            if !base.base_type().is_numeric() {
+                return Err(format!("'where' needs a numeric base type, not {}", base.base_type()));
+            }
+            return Ok(TypeDefinition::Refined(Box::new(base), parse_range(range.trim())?));
+        }
+        if let Some(pattern) = refinement.strip_prefix("matching") {
+            let pattern = pattern.trim();
+            let unquoted = crate::aaml::AAML::unwrap_quotes(pattern);
+            if unquoted.len() == pattern.len() {
+                return Err("expected a quoted pattern after 'matching'".into());
+            }
+            // The whole value has to match, not just a part of it.
+            let re = Regex::new(&format!("^(?:{})$", unquoted)).map_err(|e| e.to_string())?;
+            return Ok(TypeDefinition::Refined(Box::new(base), Constraint::Pattern(re)));
+        }
+        Err(format!("expected 'where' or 'matching' after the base type, got '{}'", refinement))
+    }
+
+    fn parse_base(aaml: &crate::aaml::AAML, name: &str) -> Result<Self, String> {
+        if let Some(target) = aaml.type_handle(name) {
+            Ok(TypeDefinition::Alias(name.to_string(), target))
+        } else if name.contains("::") {
+            resolve_builtin(name).map_err(|_| format!("unknown type '{}'", name))?;
+            Ok(TypeDefinition::Builtin(name.to_string()))
+        } else {
+            PrimitiveType::from_name(name).map_err(|_| format!("unknown type '{}'", name))?;
+            Ok(TypeDefinition::Primitive(name.to_string()))
         }
     }
 }
 
+fn strip_enum(definition: &str) -> Option<&str> {
+    definition.strip_prefix("enum").filter(|rest| rest.trim_start().starts_with('{'))
+}
+
+/// Name of the type a `@type` definition is built on; `None` for an enum.
+pub(crate) fn base_name(definition: &str) -> Option<&str> {
+    if strip_enum(definition).is_some() {
+        return None;
+    }
+    definition.split(char::is_whitespace).next()
+}
+
+/// Rewrites the base type name of a `@type` definition with `rename`, leaving enums and
+/// the refinement untouched. Used when a type's target is moved under a namespace.
+pub(crate) fn rename_base(definition: &str, rename: impl FnOnce(&str) -> Option<String>) -> String {
+    match base_name(definition).and_then(|base| Some((base.len(), rename(base)?))) {
+        Some((end, base)) => format!("{}{}", base, &definition[end..]),
+        None => definition.to_string(),
+    }
+}
+
 pub struct TypeCommand;
 
 impl Command for TypeCommand {
@@ -74,14 +188,14 @@ impl Command for TypeCommand {
             });
         }
 
-        let type_def = if definition.contains("::") {
-            TypeDefinition::Builtin(definition.to_string())
-        } else {
-            TypeDefinition::Primitive(definition.to_string())
-        };
-
-        aaml.register_type(name.to_string(), type_def);
+        let invalid = |details: String| AamlError::InvalidType { type_name: name.to_string(), details };
+        if let Some(base) = base_name(definition)
+            && aaml.type_depends_on(base, name)
+        {
+            return Err(invalid(format!("'{}' cannot be defined in terms of itself", name)));
+        }
+        let type_def = TypeDefinition::parse(aaml, definition).map_err(invalid)?;
 
-        Ok(())
+        aaml.define_type(name, type_def, definition)
     }
-}
\ No newline at end of file
+}
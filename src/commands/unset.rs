@@ -0,0 +1,28 @@
+use crate::aaml::AAML;
+use crate::commands::Command;
+use crate::error::AamlError;
+
+/// `@unset key1, key2`: removes keys defined so far, e.g. ones inherited through `@derive`
+/// or `@import`. Keys that do not exist are ignored.
+pub struct UnsetCommand;
+
+impl Command for UnsetCommand {
+    fn name(&self) -> &str { "unset" }
+
+    fn execute(&self, aaml: &mut AAML, args: &str) -> Result<(), AamlError> {
+        let keys = parse_keys(args);
+        if keys.is_empty() {
+            return Err(AamlError::DirectiveError("unset".into(), "Missing key".into()));
+        }
+
+        for key in keys {
+            aaml.remove_entry(key);
+        }
+        Ok(())
+    }
+}
+
+/// Splits a comma-separated key list, dropping empty items.
+pub(crate) fn parse_keys(args: &str) -> Vec<&str> {
+    args.split(',').map(str::trim).filter(|k| !k.is_empty()).collect()
+}
@@ -0,0 +1,400 @@
+//! Versioned binary form of a parsed config, see [`AAML::compile`](crate::aaml::AAML::compile).
+//!
+//! Layout (integers little-endian, strings as `u32` length + UTF-8 bytes):
+//!
+//! ```text
+//! "AAMC" | version: u16 | checksum: u64 (FNV-1a of the body) | body
+//! body = sources | lookups | context | entries | types | schemas | profiles | namespaces
+//! sources = u32 count, then (path, len: u64, mtime secs: u64, mtime nanos: u32)
+//! lookups = u32 count, then (glob pattern or optional import path, u32 count, then
+//!           matched path, sorted)
+//! context = u32 count, then (var name, value) sorted by name,
+//!           u32 count, then active profile in priority order,
+//!           u32 count, then (env var name, set: u32 0 or 1, value) for `$NAME` conditions
+//! entries = u32 count, then (key, value, source: u32 or u32::MAX, line: u32) in definition order
+//! types   = u32 count, then (name, definition)
+//! schemas = u32 count, then (name, extends or "", u32 mixin count, then name,
+//!           u32 field count, then (field, declaration))
+//!           field has a trailing `?` when optional; declaration is the schema syntax
+//!           after the colon, e.g. `i32 (1..=65535) = "80"`
+//! profiles = u32 count, then name, in order of first appearance
+//! namespaces = u32 count, then prefix of a namespaced import, longest first
+//! ```
+
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::time::UNIX_EPOCH;
+use crate::commands::import;
+use crate::error::AamlError;
+
+/// Version written into every blob; blobs with another version are rejected.
+pub const FORMAT_VERSION: u16 = 1;
+
+const MAGIC: &[u8; 4] = b"AAMC";
+const HEADER_LEN: usize = 4 + 2 + 8;
+const NO_SOURCE: u32 = u32::MAX;
+
+/// A file read while building a config, with the metadata used to detect staleness.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct SourceFile {
+    pub path: PathBuf,
+    pub len: u64,
+    pub modified: (u64, u32),
+}
+
+impl SourceFile {
+    pub fn stat(path: &Path) -> io::Result<SourceFile> {
+        let meta = fs::metadata(path)?;
+        let modified = meta.modified()?
+            .duration_since(UNIX_EPOCH)
+            .map(|d| (d.as_secs(), d.subsec_nanos()))
+            .unwrap_or((0, 0));
+        Ok(SourceFile { path: path.to_path_buf(), len: meta.len(), modified })
+    }
+
+    /// `true` if the file still has the size and modification time recorded here.
+    pub fn is_current(&self) -> bool {
+        SourceFile::stat(&self.path).is_ok_and(|now| now == *self)
+    }
+}
+
+/// A glob pattern or `@import?` path and the files it resolved to while building a config.
+/// A new match, or an optional file that appeared since, changes the result without
+/// touching any recorded [`SourceFile`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct Lookup {
+    pub pattern: String,
+    /// Sorted; empty for an optional import that found nothing.
+    pub matches: Vec<PathBuf>,
+}
+
+impl Lookup {
+    /// `true` if the pattern still resolves to the same files.
+    pub fn is_current(&self) -> bool {
+        import::expand_glob(&self.pattern).is_ok_and(|now| now == self.matches)
+    }
+}
+
+/// Where a key was assigned: an index into the config's source list and a line number.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct SpanRecord {
+    pub source: Option<usize>,
+    pub line: usize,
+}
+
+/// Location of a key's definition, see [`AAML::definition_location`](crate::aaml::AAML::definition_location).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SourceLocation<'a> {
+    /// File the key was assigned in, or `None` for content parsed from a string.
+    pub file: Option<&'a Path>,
+    pub line: usize,
+}
+
+/// What a blob was built under besides its sources: the [`ParseOptions`](crate::options::ParseOptions)
+/// and the environment variables read by `@if` conditions.
+#[derive(Default)]
+pub(crate) struct Context<'a> {
+    pub vars: Vec<(&'a str, &'a str)>,
+    pub profiles: Vec<&'a str>,
+    pub env: Vec<(&'a str, Option<&'a str>)>,
+}
+
+impl Context<'_> {
+    /// `true` if every recorded environment variable still has the recorded value.
+    pub fn env_is_current(&self) -> bool {
+        self.env.iter().all(|&(name, value)| std::env::var(name).ok().as_deref() == value)
+    }
+}
+
+pub(crate) struct Decoded<'a> {
+    pub sources: Vec<SourceFile>,
+    pub lookups: Vec<Lookup>,
+    pub context: Context<'a>,
+    pub entries: Vec<(&'a str, &'a str, Option<SpanRecord>)>,
+    pub types: Vec<(&'a str, &'a str)>,
+    pub schemas: Vec<DecodedSchema<'a>>,
+    pub profiles: Vec<&'a str>,
+    pub namespaces: Vec<&'a str>,
+}
+
+pub(crate) struct DecodedSchema<'a> {
+    pub name: &'a str,
+    pub extends: Option<&'a str>,
+    pub mixins: Vec<&'a str>,
+    pub fields: Vec<(&'a str, &'a str)>,
+}
+
+pub(crate) struct Encoder {
+    body: Vec<u8>,
+}
+
+impl Encoder {
+    pub fn new() -> Self {
+        Encoder { body: Vec::new() }
+    }
+
+    pub fn count(&mut self, n: usize) {
+        self.u32(u32::try_from(n).expect("compiled section exceeds u32::MAX items"));
+    }
+
+    pub fn u32(&mut self, v: u32) {
+        self.body.extend_from_slice(&v.to_le_bytes());
+    }
+
+    pub fn u64(&mut self, v: u64) {
+        self.body.extend_from_slice(&v.to_le_bytes());
+    }
+
+    pub fn str(&mut self, s: &str) {
+        self.count(s.len());
+        self.body.extend_from_slice(s.as_bytes());
+    }
+
+    pub fn source(&mut self, source: &SourceFile) {
+        self.str(&source.path.to_string_lossy());
+        self.u64(source.len);
+        self.u64(source.modified.0);
+        self.u32(source.modified.1);
+    }
+
+    pub fn lookup(&mut self, lookup: &Lookup) {
+        self.str(&lookup.pattern);
+        self.count(lookup.matches.len());
+        for path in &lookup.matches {
+            self.str(&path.to_string_lossy());
+        }
+    }
+
+    pub fn context(&mut self, context: &Context<'_>) {
+        self.count(context.vars.len());
+        for (name, value) in &context.vars {
+            self.str(name);
+            self.str(value);
+        }
+        self.count(context.profiles.len());
+        for profile in &context.profiles {
+            self.str(profile);
+        }
+        self.count(context.env.len());
+        for (name, value) in &context.env {
+            self.str(name);
+            self.u32(value.is_some() as u32);
+            self.str(value.unwrap_or(""));
+        }
+    }
+
+    pub fn span(&mut self, span: Option<SpanRecord>) {
+        let (source, line) = match span {
+            Some(s) => (s.source.map_or(NO_SOURCE, |i| i as u32), s.line as u32),
+            None => (NO_SOURCE, 0),
+        };
+        self.u32(source);
+        self.u32(line);
+    }
+
+    pub fn finish(self) -> Vec<u8> {
+        let mut out = Vec::with_capacity(HEADER_LEN + self.body.len());
+        out.extend_from_slice(MAGIC);
+        out.extend_from_slice(&FORMAT_VERSION.to_le_bytes());
+        out.extend_from_slice(&checksum(&self.body).to_le_bytes());
+        out.extend_from_slice(&self.body);
+        out
+    }
+}
+
+struct Decoder<'a> {
+    bytes: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> Decoder<'a> {
+    fn take(&mut self, n: usize) -> Result<&'a [u8], AamlError> {
+        let end = self.pos.checked_add(n)
+            .filter(|&end| end <= self.bytes.len())
+            .ok_or_else(|| invalid("unexpected end of data"))?;
+        let slice = &self.bytes[self.pos..end];
+        self.pos = end;
+        Ok(slice)
+    }
+
+    fn u32(&mut self) -> Result<u32, AamlError> {
+        Ok(u32::from_le_bytes(self.take(4)?.try_into().unwrap()))
+    }
+
+    fn u64(&mut self) -> Result<u64, AamlError> {
+        Ok(u64::from_le_bytes(self.take(8)?.try_into().unwrap()))
+    }
+
+    fn count(&mut self) -> Result<usize, AamlError> {
+        Ok(self.u32()? as usize)
+    }
+
+    fn str(&mut self) -> Result<&'a str, AamlError> {
+        let len = self.count()?;
+        std::str::from_utf8(self.take(len)?).map_err(|_| invalid("string is not valid UTF-8"))
+    }
+}
+
+fn invalid(details: &str) -> AamlError {
+    AamlError::InvalidCompiled(details.to_string())
+}
+
+/// 64-bit FNV-1a.
+fn checksum(bytes: &[u8]) -> u64 {
+    bytes.iter().fold(0xcbf2_9ce4_8422_2325, |hash, &b| (hash ^ b as u64).wrapping_mul(0x0100_0000_01b3))
+}
+
+/// Checks magic, version and checksum, returning the body.
+fn verify(bytes: &[u8]) -> Result<&[u8], AamlError> {
+    if bytes.len() < HEADER_LEN || &bytes[..4] != MAGIC {
+        return Err(invalid("not a compiled AAM config"));
+    }
+    let version = u16::from_le_bytes([bytes[4], bytes[5]]);
+    if version != FORMAT_VERSION {
+        return Err(invalid(&format!("unsupported format version {} (expected {})", version, FORMAT_VERSION)));
+    }
+    let expected = u64::from_le_bytes(bytes[6..HEADER_LEN].try_into().unwrap());
+    let body = &bytes[HEADER_LEN..];
+    if checksum(body) != expected {
+        return Err(invalid("checksum mismatch"));
+    }
+    Ok(body)
+}
+
+fn decode_sources(d: &mut Decoder<'_>) -> Result<Vec<SourceFile>, AamlError> {
+    let count = d.count()?;
+    let mut sources = Vec::with_capacity(count.min(1024));
+    for _ in 0..count {
+        let path = PathBuf::from(d.str()?);
+        let len = d.u64()?;
+        let modified = (d.u64()?, d.u32()?);
+        sources.push(SourceFile { path, len, modified });
+    }
+    Ok(sources)
+}
+
+fn decode_lookups(d: &mut Decoder<'_>) -> Result<Vec<Lookup>, AamlError> {
+    let count = d.count()?;
+    let mut lookups = Vec::with_capacity(count.min(1024));
+    for _ in 0..count {
+        let pattern = d.str()?.to_string();
+        let match_count = d.count()?;
+        let mut matches = Vec::with_capacity(match_count.min(1024));
+        for _ in 0..match_count {
+            matches.push(PathBuf::from(d.str()?));
+        }
+        lookups.push(Lookup { pattern, matches });
+    }
+    Ok(lookups)
+}
+
+fn decode_context<'a>(d: &mut Decoder<'a>) -> Result<Context<'a>, AamlError> {
+    let count = d.count()?;
+    let mut vars = Vec::with_capacity(count.min(1024));
+    for _ in 0..count {
+        vars.push((d.str()?, d.str()?));
+    }
+    let count = d.count()?;
+    let mut profiles = Vec::with_capacity(count.min(1024));
+    for _ in 0..count {
+        profiles.push(d.str()?);
+    }
+    let count = d.count()?;
+    let mut env = Vec::with_capacity(count.min(1024));
+    for _ in 0..count {
+        let name = d.str()?;
+        let set = d.u32()?;
+        let value = d.str()?;
+        env.push((name, (set != 0).then_some(value)));
+    }
+    Ok(Context { vars, profiles, env })
+}
+
+/// What a staleness check needs from a blob: everything before the entries.
+pub(crate) struct Header<'a> {
+    pub sources: Vec<SourceFile>,
+    pub lookups: Vec<Lookup>,
+    pub context: Context<'a>,
+}
+
+impl Header<'_> {
+    /// `true` if no source file, lookup or environment variable changed since compiling.
+    pub fn is_current(&self) -> bool {
+        self.sources.iter().all(SourceFile::is_current)
+            && self.lookups.iter().all(Lookup::is_current)
+            && self.context.env_is_current()
+    }
+}
+
+/// Decodes the sources, lookups and context without the entries, for staleness checks.
+pub(crate) fn decode_header(bytes: &[u8]) -> Result<Header<'_>, AamlError> {
+    let mut d = Decoder { bytes: verify(bytes)?, pos: 0 };
+    let sources = decode_sources(&mut d)?;
+    let lookups = decode_lookups(&mut d)?;
+    Ok(Header { sources, lookups, context: decode_context(&mut d)? })
+}
+
+pub(crate) fn decode(bytes: &[u8]) -> Result<Decoded<'_>, AamlError> {
+    let mut d = Decoder { bytes: verify(bytes)?, pos: 0 };
+    let sources = decode_sources(&mut d)?;
+    let lookups = decode_lookups(&mut d)?;
+    let context = decode_context(&mut d)?;
+
+    let count = d.count()?;
+    let mut entries = Vec::with_capacity(count.min(d.bytes.len() / 16));
+    for _ in 0..count {
+        let key = d.str()?;
+        let value = d.str()?;
+        let source = d.u32()?;
+        let line = d.u32()? as usize;
+        let span = match source {
+            NO_SOURCE if line == 0 => None,
+            NO_SOURCE => Some(SpanRecord { source: None, line }),
+            i if (i as usize) < sources.len() => Some(SpanRecord { source: Some(i as usize), line }),
+            _ => return Err(invalid("span refers to an unknown source")),
+        };
+        entries.push((key, value, span));
+    }
+
+    let count = d.count()?;
+    let mut types = Vec::with_capacity(count.min(1024));
+    for _ in 0..count {
+        types.push((d.str()?, d.str()?));
+    }
+
+    let count = d.count()?;
+    let mut schemas = Vec::with_capacity(count.min(1024));
+    for _ in 0..count {
+        let name = d.str()?;
+        let extends = Some(d.str()?).filter(|parent| !parent.is_empty());
+        let mixin_count = d.count()?;
+        let mut mixins = Vec::with_capacity(mixin_count.min(1024));
+        for _ in 0..mixin_count {
+            mixins.push(d.str()?);
+        }
+        let field_count = d.count()?;
+        let mut fields = Vec::with_capacity(field_count.min(1024));
+        for _ in 0..field_count {
+            fields.push((d.str()?, d.str()?));
+        }
+        schemas.push(DecodedSchema { name, extends, mixins, fields });
+    }
+
+    let count = d.count()?;
+    let mut profiles = Vec::with_capacity(count.min(1024));
+    for _ in 0..count {
+        profiles.push(d.str()?);
+    }
+
+    let count = d.count()?;
+    let mut namespaces = Vec::with_capacity(count.min(1024));
+    for _ in 0..count {
+        namespaces.push(d.str()?);
+    }
+
+    if d.pos != d.bytes.len() {
+        return Err(invalid("trailing data after namespaces"));
+    }
+    Ok(Decoded { sources, lookups, context, entries, types, schemas, profiles, namespaces })
+}
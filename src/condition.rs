@@ -0,0 +1,202 @@
+//! `@if` / `@elif` / `@else` / `@endif` and `@profile name { ... }` blocks.
+//!
+//! A condition is a `||` of `&&` terms; each term is `a == b`, `a != b`, `a` or `!a`.
+//! Quoted strings and numbers are literals; `$NAME` (an environment variable) and any other
+//! word are looked up by the caller (parse options, then keys defined so far). An
+//! undefined name equals nothing and is false; defined values are false when empty, `false`
+//! or `0`.
+
+use crate::lexer::split_outside_quotes;
+
+/// Whether `name` is one of the conditional directives.
+pub(crate) fn is_conditional(name: &str) -> bool {
+    matches!(name, "if" | "elif" | "else" | "endif")
+}
+
+enum Kind {
+    If { taken: bool, seen_else: bool },
+    /// `@profile name {`; `rank` is the profile's priority when it is active.
+    Profile { rank: Option<usize> },
+}
+
+/// Priority of the active profile that set a value, and the file it was set from: the
+/// file with the `@profile` block or `key@profile` override.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct ProfileRank {
+    pub rank: usize,
+    pub scope: usize,
+}
+
+struct Frame {
+    line: usize,
+    parent_active: bool,
+    active: bool,
+    kind: Kind,
+}
+
+/// Open `@if` and `@profile` blocks of the file being parsed.
+#[derive(Default)]
+pub(crate) struct ConditionStack {
+    frames: Vec<Frame>,
+    /// Identifies the file being parsed, for [`ProfileRank::scope`].
+    scope: usize,
+    /// Profile rank of the block an `@import` appeared in, applied to the imported file.
+    inherited_rank: Option<ProfileRank>,
+}
+
+impl ConditionStack {
+    /// A stack for a file imported at the current position; `scope` identifies that file.
+    pub(crate) fn nested(&self, scope: usize) -> Self {
+        ConditionStack { frames: Vec::new(), scope, inherited_rank: self.profile_rank() }
+    }
+
+    pub(crate) fn scope(&self) -> usize {
+        self.scope
+    }
+
+    /// Whether an `@if` or `@profile` block is open in the current file.
+    pub(crate) fn has_open_block(&self) -> bool {
+        !self.frames.is_empty()
+    }
+
+    /// Whether lines at the current position should be applied.
+    pub(crate) fn is_active(&self) -> bool {
+        self.frames.last().is_none_or(|f| f.active)
+    }
+
+    /// Rank of the innermost enclosing `@profile` block.
+    pub(crate) fn profile_rank(&self) -> Option<ProfileRank> {
+        self.frames
+            .iter()
+            .rev()
+            .find_map(|f| match f.kind {
+                Kind::Profile { rank } => Some(rank.map(|rank| ProfileRank { rank, scope: self.scope })),
+                Kind::If { .. } => None,
+            })
+            .unwrap_or(self.inherited_rank)
+    }
+
+    /// Line and directive of the innermost block that is still open.
+    pub(crate) fn unclosed(&self) -> Option<(usize, &'static str)> {
+        self.frames.last().map(|f| match f.kind {
+            Kind::If { .. } => (f.line, "Unterminated @if: missing @endif"),
+            Kind::Profile { .. } => (f.line, "Unterminated @profile: missing '}'"),
+        })
+    }
+
+    /// Opens a `@profile` block; `rank` is `None` when the profile is not active.
+    pub(crate) fn open_profile(&mut self, line: usize, rank: Option<usize>) {
+        let parent_active = self.is_active();
+        let active = parent_active && rank.is_some();
+        self.frames.push(Frame { line, parent_active, active, kind: Kind::Profile { rank } });
+    }
+
+    /// Closes a `@profile` block at a `}` line.
+    pub(crate) fn close_profile(&mut self) -> Result<(), String> {
+        match self.frames.last() {
+            Some(Frame { kind: Kind::Profile { .. }, .. }) => {
+                self.frames.pop();
+                Ok(())
+            }
+            Some(_) => Err("'}' inside @if block: missing @endif".into()),
+            None => Err("'}' without @profile".into()),
+        }
+    }
+
+    /// Applies one conditional directive. `eval` is only called when the branch could be
+    /// taken, so conditions inside skipped blocks are never evaluated.
+    pub(crate) fn apply(
+        &mut self,
+        name: &str,
+        line: usize,
+        eval: impl FnOnce() -> Result<bool, String>,
+    ) -> Result<(), String> {
+        if name == "if" {
+            let parent_active = self.is_active();
+            let taken = parent_active && eval()?;
+            let kind = Kind::If { taken, seen_else: false };
+            self.frames.push(Frame { line, parent_active, active: taken, kind });
+            return Ok(());
+        }
+
+        let frame = match self.frames.last_mut() {
+            Some(frame @ Frame { kind: Kind::If { .. }, .. }) => frame,
+            Some(_) => return Err(format!("@{} inside @profile block: missing '}}'", name)),
+            None => return Err(format!("@{} without @if", name)),
+        };
+        let Kind::If { taken, seen_else } = &mut frame.kind else { unreachable!() };
+
+        match name {
+            "elif" => {
+                if *seen_else {
+                    return Err("@elif after @else".into());
+                }
+                frame.active = frame.parent_active && !*taken && eval()?;
+                *taken |= frame.active;
+            }
+            "else" => {
+                if *seen_else {
+                    return Err("Duplicate @else".into());
+                }
+                *seen_else = true;
+                frame.active = frame.parent_active && !*taken;
+                *taken = true;
+            }
+            "endif" => {
+                self.frames.pop();
+            }
+            _ => unreachable!("not a conditional directive: {}", name),
+        }
+        Ok(())
+    }
+}
+
+/// Evaluates a condition, resolving bare names with `lookup`.
+pub(crate) fn evaluate(expr: &str, lookup: &dyn Fn(&str) -> Option<String>) -> Result<bool, String> {
+    if expr.trim().is_empty() {
+        return Err("Missing condition".into());
+    }
+
+    let mut result = false;
+    for alternative in split_outside_quotes(expr, "||") {
+        let mut all = true;
+        for term in split_outside_quotes(alternative, "&&") {
+            all &= term_is_true(term.trim(), lookup)?;
+        }
+        result |= all;
+    }
+    Ok(result)
+}
+
+fn term_is_true(term: &str, lookup: &dyn Fn(&str) -> Option<String>) -> Result<bool, String> {
+    for op in ["==", "!="] {
+        if let [lhs, rhs] = split_outside_quotes(term, op)[..] {
+            let equal = matches!((operand(lhs, lookup)?, operand(rhs, lookup)?), (Some(l), Some(r)) if l == r);
+            return Ok(equal == (op == "=="));
+        }
+    }
+
+    match term.strip_prefix('!') {
+        Some(rest) => Ok(!truthy(operand(rest, lookup)?)),
+        None => Ok(truthy(operand(term, lookup)?)),
+    }
+}
+
+fn operand(raw: &str, lookup: &dyn Fn(&str) -> Option<String>) -> Result<Option<String>, String> {
+    let s = raw.trim();
+    let quoted = s.len() >= 2 && (s.starts_with('"') && s.ends_with('"') || s.starts_with('\'') && s.ends_with('\''));
+
+    if quoted {
+        Ok(Some(s[1..s.len() - 1].to_string()))
+    } else if s.is_empty() || s.contains(|c: char| c.is_whitespace() || matches!(c, '"' | '\'' | '=' | '!')) {
+        Err(format!("Invalid operand '{}'", s))
+    } else if s.parse::<f64>().is_ok() {
+        Ok(Some(s.to_string()))
+    } else {
+        Ok(lookup(s))
+    }
+}
+
+fn truthy(value: Option<String>) -> bool {
+    value.is_some_and(|v| !matches!(v.as_str(), "" | "false" | "0"))
+}
@@ -0,0 +1,50 @@
+use serde_json::{Map, Number};
+use crate::aaml::AAML;
+use crate::convert::{self, Value};
+use crate::error::AamlError;
+
+impl AAML {
+    /// Parses a JSON document, flattening nested objects into dotted keys.
+    pub fn from_json(content: &str) -> Result<Self, AamlError> {
+        let json: serde_json::Value = serde_json::from_str(content)
+            .map_err(|e| convert::parse_error(e.line(), format!("Invalid JSON: {}", e)))?;
+
+        let mut aaml = AAML::new();
+        convert::flatten_into(&mut aaml, "", &from_json_value(json))?;
+        Ok(aaml)
+    }
+
+    /// Serializes the config as pretty-printed JSON, nesting dotted keys.
+    pub fn to_json(&self) -> Result<String, AamlError> {
+        let json = to_json_value(convert::unflatten(self)?);
+        serde_json::to_string_pretty(&json).map_err(|e| AamlError::InvalidValue(e.to_string()))
+    }
+}
+
+fn from_json_value(json: serde_json::Value) -> Value {
+    match json {
+        serde_json::Value::Null => Value::Null,
+        serde_json::Value::Bool(b) => Value::Bool(b),
+        serde_json::Value::Number(n) => match n.as_i64() {
+            Some(i) => Value::Int(i),
+            None => n.as_f64().map_or_else(|| Value::Str(n.to_string()), Value::Float),
+        },
+        serde_json::Value::String(s) => Value::Str(s),
+        serde_json::Value::Array(items) => Value::List(items.into_iter().map(from_json_value).collect()),
+        serde_json::Value::Object(map) => Value::Table(map.into_iter().map(|(k, v)| (k, from_json_value(v))).collect()),
+    }
+}
+
+fn to_json_value(value: Value) -> serde_json::Value {
+    match value {
+        Value::Null => serde_json::Value::Null,
+        Value::Bool(b) => serde_json::Value::Bool(b),
+        Value::Int(i) => serde_json::Value::Number(i.into()),
+        Value::Float(f) => Number::from_f64(f).map_or(serde_json::Value::Null, serde_json::Value::Number),
+        Value::Str(s) => serde_json::Value::String(s),
+        Value::List(items) => serde_json::Value::Array(items.into_iter().map(to_json_value).collect()),
+        Value::Table(entries) => {
+            serde_json::Value::Object(entries.into_iter().map(|(k, v)| (k, to_json_value(v))).collect::<Map<_, _>>())
+        }
+    }
+}
@@ -0,0 +1,307 @@
+//! Converters between AAM and other config formats (`json`, `toml`, `yaml` features).
+//!
+//! Importing flattens nested objects into dotted keys (`db.host = ...`). Arrays of scalars
+//! become bracketed lists (`ports = [80, 443]`, with `null` items written as `null`), other
+//! arrays use index segments (`servers.0.host = ...`), and `null` becomes an empty value.
+//! Empty objects have no keys to flatten into and are dropped. Strings are stored as
+//! written; one that would read back as something else (`"true"`, `"1"`, `""`, `"1, 2"`)
+//! also gets a `string` type named after its key. List items containing commas, quotes or
+//! brackets are double-quoted with `\` and `"` escaped.
+//!
+//! Exporting rebuilds the nesting from the dotted keys (tables whose keys are exactly
+//! `0..n` become arrays) and emits typed values: an empty value is `null` (an empty string
+//! in TOML) and a quoted one is a string; a key whose full name is a registered type, or
+//! whose last segment is one that does not also name a key, uses that type's base type
+//! (`string`, `i32`, `f64`, `bool`, `f64` lists); other values are typed only when they are
+//! canonical numbers, booleans, or lists of those.
+
+#[cfg(feature = "json")]
+mod json;
+#[cfg(feature = "toml")]
+mod toml;
+#[cfg(feature = "yaml")]
+mod yaml;
+
+use crate::aaml::AAML;
+use crate::error::AamlError;
+use crate::types::primitive_type::PrimitiveType;
+
+/// Format-neutral document tree.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) enum Value {
+    Null,
+    Bool(bool),
+    Int(i64),
+    Float(f64),
+    Str(String),
+    List(Vec<Value>),
+    Table(Vec<(String, Value)>),
+}
+
+impl Value {
+    fn is_scalar(&self) -> bool {
+        !matches!(self, Value::List(_) | Value::Table(_))
+    }
+
+    fn to_aam(&self) -> String {
+        match self {
+            Value::Null => String::new(),
+            Value::Bool(b) => b.to_string(),
+            Value::Int(n) => n.to_string(),
+            Value::Float(f) => format_float(*f),
+            Value::Str(s) => s.clone(),
+            Value::List(items) => format!("[{}]", items.iter().map(Value::item_to_aam).collect::<Vec<_>>().join(", ")),
+            Value::Table(_) => String::new(),
+        }
+    }
+
+    /// Like [`to_aam`](Self::to_aam) for an item of a bracketed list, where `null` is
+    /// written out.
+    fn item_to_aam(&self) -> String {
+        match self {
+            Value::Null => "null".to_string(),
+            Value::Str(s) if s.is_empty() || s == "null" || s.trim() != s => quote(s),
+            Value::Str(s) if s.contains([',', '[', ']', '"']) || infer_scalar(s) != *self => quote(s),
+            other => other.to_aam(),
+        }
+    }
+}
+
+/// `{:?}` with a `.0` mantissa before any exponent, so that the value reads back as a float.
+fn format_float(f: f64) -> String {
+    let s = format!("{:?}", f);
+    match s.split_once('e') {
+        Some((mantissa, exp)) if !mantissa.contains('.') => format!("{}.0e{}", mantissa, exp),
+        _ => s,
+    }
+}
+
+fn quote(s: &str) -> String {
+    format!("\"{}\"", s.replace('\\', "\\\\").replace('"', "\\\""))
+}
+
+/// Reverses [`quote`]; `None` unless `value` is exactly one quoted string.
+fn unquote(value: &str) -> Option<String> {
+    let inner = value.strip_prefix('"')?.strip_suffix('"')?;
+    let mut out = String::with_capacity(inner.len());
+    let mut chars = inner.chars();
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' => out.push(chars.next().filter(|c| matches!(c, '\\' | '"'))?),
+            '"' => return None,
+            c => out.push(c),
+        }
+    }
+    Some(out)
+}
+
+/// Splits the inside of `[...]` at commas outside quoted items and types each item;
+/// `None` if an unquoted item contains a quote or bracket.
+fn parse_items(inner: &str) -> Option<Vec<Value>> {
+    if inner.trim().is_empty() {
+        return Some(Vec::new());
+    }
+    let mut items = Vec::new();
+    let (mut start, mut quoted, mut escaped) = (0, false, false);
+    for (i, c) in inner.char_indices() {
+        match c {
+            _ if escaped => escaped = false,
+            '\\' if quoted => escaped = true,
+            '"' => quoted = !quoted,
+            ',' if !quoted => {
+                items.push(parse_item(&inner[start..i])?);
+                start = i + 1;
+            }
+            _ => {}
+        }
+    }
+    items.push(parse_item(&inner[start..])?);
+    Some(items)
+}
+
+fn parse_item(item: &str) -> Option<Value> {
+    let item = item.trim();
+    if item.starts_with('"') {
+        return unquote(item).map(Value::Str);
+    }
+    if item.contains(['"', '[', ']']) {
+        return None;
+    }
+    Some(if item == "null" { Value::Null } else { infer_scalar(item) })
+}
+
+/// Inserts `value` into `aaml` under `prefix`, flattening nested tables and lists.
+pub(crate) fn flatten_into(aaml: &mut AAML, prefix: &str, value: &Value) -> Result<(), AamlError> {
+    let child = |key: &str| if prefix.is_empty() { key.to_string() } else { format!("{}.{}", prefix, key) };
+
+    match value {
+        Value::Table(entries) => {
+            for (key, v) in entries {
+                flatten_into(aaml, &child(key), v)?;
+            }
+            Ok(())
+        }
+        Value::List(items) if !items.iter().all(Value::is_scalar) => {
+            for (i, v) in items.iter().enumerate() {
+                flatten_into(aaml, &child(&i.to_string()), v)?;
+            }
+            Ok(())
+        }
+        Value::Str(s) if infer(s) != *value => {
+            aaml.insert_entry(prefix, s)?;
+            let string = crate::commands::typecm::TypeDefinition::Primitive("string".to_string());
+            aaml.define_type(prefix, string, "string")
+        }
+        scalar => aaml.insert_entry(prefix, &scalar.to_aam()),
+    }
+}
+
+/// Rebuilds a nested tree from the dotted keys of `aaml`, in definition order.
+pub(crate) fn unflatten(aaml: &AAML) -> Result<Value, AamlError> {
+    let mut root = Vec::new();
+    for e in aaml.ordered_entries() {
+        let segments: Vec<&str> = e.key.split('.').collect();
+        insert_path(&mut root, &segments, typed_value(aaml, e.key, e.value), e.key)?;
+    }
+    Ok(listify(Value::Table(root)))
+}
+
+fn insert_path(table: &mut Vec<(String, Value)>, path: &[&str], value: Value, full_key: &str) -> Result<(), AamlError> {
+    let conflict = || AamlError::InvalidValue(format!(
+        "Key '{}' conflicts with another key that uses it as both a value and a table", full_key
+    ));
+
+    let (head, rest) = path.split_first().expect("split always yields a segment");
+    let pos = table.iter().position(|(k, _)| k == head);
+
+    if rest.is_empty() {
+        return match pos {
+            Some(_) => Err(conflict()),
+            None => {
+                table.push((head.to_string(), value));
+                Ok(())
+            }
+        };
+    }
+
+    let idx = match pos {
+        Some(i) => i,
+        None => {
+            table.push((head.to_string(), Value::Table(Vec::new())));
+            table.len() - 1
+        }
+    };
+    match &mut table[idx].1 {
+        Value::Table(inner) => insert_path(inner, rest, value, full_key),
+        _ => Err(conflict()),
+    }
+}
+
+/// Turns tables whose keys are exactly `0..n` back into lists.
+fn listify(value: Value) -> Value {
+    match value {
+        Value::Table(entries) => {
+            let is_list = !entries.is_empty()
+                && entries.iter().enumerate().all(|(i, (k, _))| *k == i.to_string());
+            let entries: Vec<_> = entries.into_iter().map(|(k, v)| (k, listify(v))).collect();
+            if is_list {
+                Value::List(entries.into_iter().map(|(_, v)| v).collect())
+            } else {
+                Value::Table(entries)
+            }
+        }
+        other => other,
+    }
+}
+
+fn typed_value(aaml: &AAML, key: &str, value: &str) -> Value {
+    let last = key.rsplit('.').next().unwrap_or(key);
+    // A type named after another key describes that key, not every `*.last`.
+    let declared = aaml.get_type(key)
+        .or_else(|| aaml.get_type(last).filter(|_| !aaml.contains_key(last)))
+        .map(|t| t.base_type());
+    if declared == Some(PrimitiveType::String) {
+        return Value::Str(value.to_string());
+    }
+    if let Some(s) = unquote(value) {
+        return Value::Str(s);
+    }
+
+    let typed = match declared {
+        Some(t) if t.is_integer() => value.trim().parse::<i64>().ok().map(Value::Int),
+        Some(t) if t.is_float() && (value.contains(',') || value.starts_with('[')) => {
+            let items = value.strip_prefix('[').and_then(|v| v.strip_suffix(']')).unwrap_or(value);
+            parse_list(items, |p| p.parse::<f64>().ok().map(Value::Float))
+        }
+        Some(t) if t.is_float() => value.trim().parse::<f64>().ok().map(Value::Float),
+        Some(PrimitiveType::Bool) => match value.trim().to_lowercase().as_str() {
+            "true" | "1" => Some(Value::Bool(true)),
+            "false" | "0" => Some(Value::Bool(false)),
+            _ => None,
+        },
+        Some(_) => Some(Value::Str(value.to_string())),
+        None => None,
+    };
+
+    typed.unwrap_or_else(|| match declared {
+        Some(_) => Value::Str(value.to_string()),
+        None => infer(value),
+    })
+}
+
+fn parse_list(value: &str, item: impl Fn(&str) -> Option<Value>) -> Option<Value> {
+    if value.trim().is_empty() {
+        return Some(Value::List(Vec::new()));
+    }
+    value.split(',').map(|p| item(p.trim())).collect::<Option<Vec<_>>>().map(Value::List)
+}
+
+/// Types an untyped value only when it is unambiguous: empty values (`null`), quoted
+/// strings, canonical integers, decimal floats, `true`/`false`, bracketed lists, and
+/// comma-separated lists of numbers and booleans.
+fn infer(value: &str) -> Value {
+    if value.is_empty() {
+        return Value::Null;
+    }
+    if let Some(s) = unquote(value) {
+        return Value::Str(s);
+    }
+    if let Some(items) = value.strip_prefix('[').and_then(|v| v.strip_suffix(']')).and_then(parse_items) {
+        return Value::List(items);
+    }
+    if value.contains(',') {
+        let parts: Vec<&str> = value.split(',').map(str::trim).collect();
+        if parts.iter().all(|p| !matches!(infer_scalar(p), Value::Str(_))) {
+            return Value::List(parts.into_iter().map(infer_scalar).collect());
+        }
+        return Value::Str(value.to_string());
+    }
+    infer_scalar(value)
+}
+
+fn infer_scalar(value: &str) -> Value {
+    match value {
+        "true" => return Value::Bool(true),
+        "false" => return Value::Bool(false),
+        _ => {}
+    }
+    if let Ok(n) = value.parse::<i64>()
+        && n.to_string() == value
+    {
+        return Value::Int(n);
+    }
+    let (mantissa, exponent) = value.split_once('e').unwrap_or((value, "0"));
+    let plain_decimal = mantissa.contains('.')
+        && mantissa.trim_start_matches('-').chars().all(|c| c.is_ascii_digit() || c == '.')
+        && !mantissa.trim_start_matches('-').starts_with('.')
+        && !mantissa.ends_with('.')
+        && exponent.parse::<i32>().is_ok();
+    if plain_decimal && let Ok(f) = value.parse::<f64>() {
+        return Value::Float(f);
+    }
+    Value::Str(value.to_string())
+}
+
+fn parse_error(line: usize, details: impl Into<String>) -> AamlError {
+    AamlError::ParseError { line, content: String::new(), details: details.into() }
+}
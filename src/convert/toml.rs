@@ -0,0 +1,52 @@
+use crate::aaml::AAML;
+use crate::convert::{self, Value};
+use crate::error::AamlError;
+
+impl AAML {
+    /// Parses a TOML document, flattening nested tables into dotted keys.
+    pub fn from_toml(content: &str) -> Result<Self, AamlError> {
+        let table: toml::Table = content.parse().map_err(|e: toml::de::Error| {
+            let line = e.span().map_or(0, |span| content[..span.start].lines().count().max(1));
+            convert::parse_error(line, format!("Invalid TOML: {}", e.message()))
+        })?;
+
+        let mut aaml = AAML::new();
+        convert::flatten_into(&mut aaml, "", &from_toml_value(toml::Value::Table(table)))?;
+        Ok(aaml)
+    }
+
+    /// Serializes the config as TOML, nesting dotted keys into tables. Empty values are
+    /// written as empty strings since TOML has no `null`.
+    pub fn to_toml(&self) -> Result<String, AamlError> {
+        match to_toml_value(convert::unflatten(self)?) {
+            toml::Value::Table(table) => toml::to_string(&table).map_err(|e| AamlError::InvalidValue(e.to_string())),
+            _ => unreachable!("unflatten always returns a table"),
+        }
+    }
+}
+
+fn from_toml_value(value: toml::Value) -> Value {
+    match value {
+        toml::Value::String(s) => Value::Str(s),
+        toml::Value::Integer(i) => Value::Int(i),
+        toml::Value::Float(f) => Value::Float(f),
+        toml::Value::Boolean(b) => Value::Bool(b),
+        toml::Value::Datetime(d) => Value::Str(d.to_string()),
+        toml::Value::Array(items) => Value::List(items.into_iter().map(from_toml_value).collect()),
+        toml::Value::Table(table) => Value::Table(table.into_iter().map(|(k, v)| (k, from_toml_value(v))).collect()),
+    }
+}
+
+fn to_toml_value(value: Value) -> toml::Value {
+    match value {
+        Value::Null => toml::Value::String(String::new()),
+        Value::Bool(b) => toml::Value::Boolean(b),
+        Value::Int(i) => toml::Value::Integer(i),
+        Value::Float(f) => toml::Value::Float(f),
+        Value::Str(s) => toml::Value::String(s),
+        Value::List(items) => toml::Value::Array(items.into_iter().map(to_toml_value).collect()),
+        Value::Table(entries) => {
+            toml::Value::Table(entries.into_iter().map(|(k, v)| (k, to_toml_value(v))).collect())
+        }
+    }
+}
@@ -0,0 +1,65 @@
+use yaml_rust2::{Yaml, YamlEmitter, YamlLoader};
+use yaml_rust2::yaml::Hash;
+use crate::aaml::AAML;
+use crate::convert::{self, Value};
+use crate::error::AamlError;
+
+impl AAML {
+    /// Parses the first document of a YAML stream, flattening nested mappings into dotted
+    /// keys. Non-string mapping keys are converted to their text form.
+    pub fn from_yaml(content: &str) -> Result<Self, AamlError> {
+        let docs = YamlLoader::load_from_str(content)
+            .map_err(|e| convert::parse_error(e.marker().line(), format!("Invalid YAML: {}", e)))?;
+
+        let mut aaml = AAML::new();
+        if let Some(doc) = docs.into_iter().next() {
+            convert::flatten_into(&mut aaml, "", &from_yaml_value(doc))?;
+        }
+        Ok(aaml)
+    }
+
+    /// Serializes the config as YAML, nesting dotted keys into mappings.
+    pub fn to_yaml(&self) -> Result<String, AamlError> {
+        let yaml = to_yaml_value(convert::unflatten(self)?);
+        let mut out = String::new();
+        YamlEmitter::new(&mut out).dump(&yaml).map_err(|e| AamlError::InvalidValue(e.to_string()))?;
+        out.push('\n');
+        Ok(out)
+    }
+}
+
+fn from_yaml_value(yaml: Yaml) -> Value {
+    match yaml {
+        Yaml::Null | Yaml::BadValue => Value::Null,
+        Yaml::Boolean(b) => Value::Bool(b),
+        Yaml::Integer(i) => Value::Int(i),
+        Yaml::Real(s) => s.parse::<f64>().map_or(Value::Str(s), Value::Float),
+        Yaml::String(s) => Value::Str(s),
+        Yaml::Array(items) => Value::List(items.into_iter().map(from_yaml_value).collect()),
+        Yaml::Hash(hash) => Value::Table(hash.into_iter().map(|(k, v)| (key_text(k), from_yaml_value(v))).collect()),
+        Yaml::Alias(_) => Value::Null,
+    }
+}
+
+fn key_text(key: Yaml) -> String {
+    match key {
+        Yaml::String(s) | Yaml::Real(s) => s,
+        Yaml::Integer(i) => i.to_string(),
+        Yaml::Boolean(b) => b.to_string(),
+        _ => String::new(),
+    }
+}
+
+fn to_yaml_value(value: Value) -> Yaml {
+    match value {
+        Value::Null => Yaml::Null,
+        Value::Bool(b) => Yaml::Boolean(b),
+        Value::Int(i) => Yaml::Integer(i),
+        Value::Float(f) => Yaml::Real(format!("{:?}", f)),
+        Value::Str(s) => Yaml::String(s),
+        Value::List(items) => Yaml::Array(items.into_iter().map(to_yaml_value).collect()),
+        Value::Table(entries) => {
+            Yaml::Hash(entries.into_iter().map(|(k, v)| (Yaml::String(k), to_yaml_value(v))).collect::<Hash>())
+        }
+    }
+}
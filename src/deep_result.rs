@@ -0,0 +1,49 @@
+use crate::found_value::FoundValue;
+
+/// Outcome of following a chain of aliases with `AAML::find_deep_trace`.
+///
+/// Every variant carries `chain`: the keys visited, starting with the requested key.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DeepResult {
+    /// The chain ended at a value that is not itself a key.
+    Resolved {
+        chain: Vec<String>,
+        value: FoundValue,
+    },
+    /// The chain came back to a key it had already visited.
+    /// `cycle` holds the keys forming the loop, starting at the first repeated one.
+    Cycle {
+        chain: Vec<String>,
+        cycle: Vec<String>,
+    },
+    /// The requested key is not defined, so there is nothing to follow.
+    Dangling {
+        chain: Vec<String>,
+    },
+}
+
+impl DeepResult {
+    pub fn chain(&self) -> &[String] {
+        match self {
+            DeepResult::Resolved { chain, .. }
+            | DeepResult::Cycle { chain, .. }
+            | DeepResult::Dangling { chain } => chain,
+        }
+    }
+
+    /// The resolved value, if the chain terminated without a loop.
+    pub fn value(&self) -> Option<&FoundValue> {
+        match self {
+            DeepResult::Resolved { value, .. } => Some(value),
+            _ => None,
+        }
+    }
+
+    pub fn is_resolved(&self) -> bool {
+        matches!(self, DeepResult::Resolved { .. })
+    }
+
+    pub fn is_cycle(&self) -> bool {
+        matches!(self, DeepResult::Cycle { .. })
+    }
+}
@@ -1,7 +1,9 @@
 use std::fmt;
 use std::io;
 
+/// New variants may be added in minor releases, so matches need a wildcard arm.
 #[derive(Debug)]
+#[non_exhaustive]
 pub enum AamlError {
     IoError(io::Error),
     ParseError {
@@ -16,6 +18,17 @@ pub enum AamlError {
         details: String,
     },
     DirectiveError(String, String),
+    AmbiguousLookup {
+        value: String,
+        keys: Vec<String>,
+    },
+    InvalidCompiled(String),
+    MissingKeys(Vec<String>),
+    SchemaViolation {
+        schema: String,
+        key: String,
+        details: String,
+    },
 }
 
 impl fmt::Display for AamlError {
@@ -33,6 +46,14 @@ impl fmt::Display for AamlError {
             AamlError::DirectiveError(cmd, msg) => {
                 write!(f, "Directive '@{}' error: {}", cmd, msg)
             }
+            AamlError::AmbiguousLookup { value, keys } => {
+                write!(f, "Ambiguous lookup: value '{}' is held by keys {}", value, keys.join(", "))
+            }
+            AamlError::InvalidCompiled(msg) => write!(f, "Invalid compiled config: {}", msg),
+            AamlError::MissingKeys(keys) => write!(f, "Missing required keys: {}", keys.join(", ")),
+            AamlError::SchemaViolation { schema, key, details } => {
+                write!(f, "Schema '{}' violated by '{}': {}", schema, key, details)
+            }
         }
     }
 }
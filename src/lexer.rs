@@ -0,0 +1,114 @@
+//! Line-level lexer shared by every parsing front end.
+
+/// One lexed source line.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Line<'a> {
+    /// Blank or comment-only line.
+    Empty,
+    Assignment { key: &'a str, value: &'a str },
+    /// Directive body without the leading `@`.
+    Directive(&'a str),
+    /// `}` closing a `@profile` block. With no block open the parser treats it as an
+    /// ordinary line, see [`lex_assignment`].
+    BlockEnd,
+    Invalid { content: &'a str, details: &'static str },
+}
+
+pub(crate) fn lex_line(raw_line: &str) -> Line<'_> {
+    let line = strip_comment(raw_line).trim();
+
+    if line.is_empty() {
+        return Line::Empty;
+    }
+
+    if let Some(rest) = line.strip_prefix('@') {
+        return Line::Directive(rest);
+    }
+
+    if line == "}" {
+        return Line::BlockEnd;
+    }
+
+    lex_assignment(line)
+}
+
+/// Lexes a comment-free, trimmed line that is neither a directive nor a block end.
+pub(crate) fn lex_assignment(line: &str) -> Line<'_> {
+    match parse_assignment(line) {
+        Ok((key, value)) => Line::Assignment { key, value },
+        Err(details) => Line::Invalid { content: line, details },
+    }
+}
+
+/// Splits a directive body into its command name and the remaining arguments.
+pub(crate) fn split_directive(content: &str) -> (&str, &str) {
+    let mut parts = content.splitn(2, char::is_whitespace);
+    let command_name = parts.next().unwrap_or("").trim();
+    let args = parts.next().unwrap_or("");
+    (command_name, args)
+}
+
+/// Splits `s` at every `sep` that is not inside quotes.
+pub(crate) fn split_outside_quotes<'a>(s: &'a str, sep: &str) -> Vec<&'a str> {
+    let mut parts = Vec::new();
+    let mut quote = None;
+    let mut start = 0;
+
+    for (idx, c) in s.char_indices() {
+        match (quote, c) {
+            (None, '"' | '\'') => quote = Some(c),
+            (Some(q), c) if c == q => quote = None,
+            (None, _) if idx >= start && s[idx..].starts_with(sep) => {
+                parts.push(&s[start..idx]);
+                start = idx + sep.len();
+            }
+            _ => {}
+        }
+    }
+    parts.push(&s[start..]);
+    parts
+}
+
+pub(crate) fn strip_comment(line: &str) -> &str {
+    split_comment(line).0
+}
+
+/// Splits a line at the first `#` outside quotes, returning the code and the comment text
+/// (without the `#`).
+pub(crate) fn split_comment(line: &str) -> (&str, Option<&str>) {
+    let mut quote_state = None;
+
+    for (idx, c) in line.char_indices() {
+         match (quote_state, c) {
+            (None, '#') => return (&line[..idx], Some(&line[idx + 1..])),
+            (None, '"' | '\'') => quote_state = Some(c),
+            (Some(q), c) if c == q => quote_state = None,
+            _ => {}
+        }
+    }
+    (line, None)
+}
+
+fn parse_assignment(line: &'_ str) -> Result<(&'_ str, &'_ str), &'static str> {
+    let (key, val) = line.split_once('=')
+        .ok_or("Missing assignment operator '='")?;
+
+    let key = key.trim();
+    if key.is_empty() {
+        return Err("Key cannot be empty");
+    }
+
+    let value = unwrap_quotes(val);
+    Ok((key, value))
+}
+
+pub(crate) fn unwrap_quotes(s: &str) -> &str {
+    let s = s.trim();
+    if s.starts_with('"') && s.ends_with('"') && s.len() >= 2 {
+        return &s[1..s.len() - 1];
+    }
+    if s.starts_with('\'') && s.ends_with('\'') && s.len() >= 2 {
+        return &s[1..s.len() - 1];
+    }
+    s
+}
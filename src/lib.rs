@@ -1,9 +1,30 @@
 pub mod aaml;
 pub mod found_value;
+pub mod deep_result;
 pub mod error;
 pub mod builder;
 pub mod commands;
+pub mod compiled;
+pub mod reader;
+pub mod snapshot;
+pub mod options;
+pub mod value;
 mod test_imports;
 mod test_core;
 mod test_derive;
-mod types;
\ No newline at end of file
+mod test_parallel;
+mod test_reader;
+mod test_snapshot;
+mod test_compiled;
+mod test_convert;
+mod test_conditional;
+mod test_profiles;
+mod test_types;
+mod types;
+mod store;
+mod lexer;
+mod condition;
+#[cfg(any(feature = "json", feature = "toml", feature = "yaml"))]
+mod convert;
+#[cfg(feature = "parallel")]
+mod parallel;
\ No newline at end of file
@@ -0,0 +1,51 @@
+use std::collections::HashMap;
+
+/// Settings applied while parsing, see [`AAML::parse_with_options`](crate::aaml::AAML::parse_with_options).
+#[derive(Debug, Clone, Default)]
+pub struct ParseOptions {
+    vars: HashMap<String, String>,
+    profiles: Vec<String>,
+}
+
+impl ParseOptions {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Defines a variable for `@if` / `@elif` conditions. Variables take precedence over
+    /// keys of the same name.
+    pub fn var(mut self, name: impl Into<String>, value: impl Into<String>) -> Self {
+        self.vars.insert(name.into(), value.into());
+        self
+    }
+
+    pub fn get_var(&self, name: &str) -> Option<&str> {
+        self.vars.get(name).map(String::as_str)
+    }
+
+    /// All variables, sorted by name.
+    pub(crate) fn sorted_vars(&self) -> Vec<(&str, &str)> {
+        let mut vars: Vec<_> = self.vars.iter().map(|(k, v)| (k.as_str(), v.as_str())).collect();
+        vars.sort();
+        vars
+    }
+
+    /// Activates a profile. Profiles added earlier take priority over later ones.
+    pub fn profile(mut self, name: impl Into<String>) -> Self {
+        let name = name.into();
+        if !self.profiles.contains(&name) {
+            self.profiles.push(name);
+        }
+        self
+    }
+
+    /// Active profiles, highest priority first.
+    pub fn profiles(&self) -> &[String] {
+        &self.profiles
+    }
+
+    /// Priority of an active profile (0 is the highest), or `None` if it is not active.
+    pub(crate) fn profile_rank(&self, name: &str) -> Option<usize> {
+        self.profiles.iter().position(|p| p == name)
+    }
+}
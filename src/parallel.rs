@@ -0,0 +1,78 @@
+//! Multi-threaded parsing helpers behind the `parallel` feature.
+//!
+//! Only the side-effect-free work runs on worker threads: lexing chunks of a large
+//! document and reading imported files. Lexed lines are still applied to the map one by
+//! one in source order, so last-write-wins and directive ordering are unchanged.
+
+use std::fs;
+use std::num::NonZeroUsize;
+use std::path::PathBuf;
+use std::thread;
+use crate::lexer::{self, Line};
+
+/// Documents smaller than this are lexed on the calling thread.
+pub(crate) const PARALLEL_THRESHOLD: usize = 1 << 20;
+
+/// Lexes `content` across worker threads. The result is in source order: chunk by chunk,
+/// line by line. Returns `None` when the document is too small or only one core is
+/// available, in which case the caller should lex it in place.
+pub(crate) fn lex_chunks(content: &str) -> Option<Vec<Vec<Line<'_>>>> {
+    let workers = thread::available_parallelism().map_or(1, NonZeroUsize::get);
+    if content.len() < PARALLEL_THRESHOLD || workers == 1 {
+        return None;
+    }
+
+    Some(lex_in_chunks(content, workers))
+}
+
+/// Lexes `content` as `workers` chunks, each on its own thread.
+pub(crate) fn lex_in_chunks(content: &str, workers: usize) -> Vec<Vec<Line<'_>>> {
+    let chunks = split_at_lines(content, workers);
+    thread::scope(|scope| {
+        let handles: Vec<_> = chunks
+            .into_iter()
+            .map(|chunk| scope.spawn(move || chunk.lines().map(lexer::lex_line).collect::<Vec<_>>()))
+            .collect();
+
+        handles
+            .into_iter()
+            .map(|h| h.join().expect("AAML lexer thread panicked"))
+            .collect()
+    })
+}
+
+/// Splits `content` into at most `parts` pieces, cutting only right after a `'\n'`.
+pub(crate) fn split_at_lines(content: &str, parts: usize) -> Vec<&str> {
+    let target = content.len().div_ceil(parts);
+    let mut chunks = Vec::with_capacity(parts);
+    let mut rest = content;
+
+    while rest.len() > target {
+        match rest[target..].find('\n') {
+            Some(pos) => {
+                let (head, tail) = rest.split_at(target + pos + 1);
+                chunks.push(head);
+                rest = tail;
+            }
+            None => break,
+        }
+    }
+    chunks.push(rest);
+    chunks
+}
+
+/// Reads every file concurrently. Files that fail to read are left out, so the caller
+/// reports the error when it reads them again itself.
+pub(crate) fn read_files(paths: &[&str]) -> Vec<(PathBuf, String)> {
+    thread::scope(|scope| {
+        let handles: Vec<_> = paths
+            .iter()
+            .map(|&path| scope.spawn(move || fs::read_to_string(path).map(|c| (PathBuf::from(path), c))))
+            .collect();
+
+        handles
+            .into_iter()
+            .filter_map(|h| h.join().ok().and_then(Result::ok))
+            .collect()
+    })
+}
@@ -0,0 +1,136 @@
+//! Event-based streaming parser.
+//!
+//! [`AamlReader`] lexes any [`BufRead`] source one line at a time and yields [`Event`]s
+//! without building a map, so huge generated files can be filtered or transformed in
+//! constant memory. Directives are reported, not executed.
+
+use std::io::BufRead;
+use crate::error::AamlError;
+use crate::lexer::{self, Line};
+
+/// Location of an event in the input.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Span {
+    /// 1-based line number.
+    pub line: usize,
+    /// Byte offset of the start of the line from the beginning of the input.
+    pub start: usize,
+    /// Byte offset of the end of the line, excluding the line terminator.
+    pub end: usize,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Event {
+    /// `key = value`, with quotes already removed from the value.
+    Assignment { key: String, value: String, span: Span },
+    /// `@name args`.
+    Directive { name: String, args: String, span: Span },
+    /// `}` closing a `@profile` block.
+    BlockEnd { span: Span },
+    /// Comment text after `#`, either on its own line or trailing another event.
+    Comment { text: String, span: Span },
+}
+
+impl Event {
+    pub fn span(&self) -> Span {
+        match self {
+            Event::Assignment { span, .. }
+            | Event::Directive { span, .. }
+            | Event::BlockEnd { span }
+            | Event::Comment { span, .. } => *span,
+        }
+    }
+}
+
+pub struct AamlReader<R> {
+    reader: R,
+    buf: String,
+    line: usize,
+    offset: usize,
+    pending: Option<Event>,
+}
+
+impl<R: BufRead> AamlReader<R> {
+    pub fn new(reader: R) -> Self {
+        AamlReader {
+            reader,
+            buf: String::new(),
+            line: 0,
+            offset: 0,
+            pending: None,
+        }
+    }
+
+    /// Current 1-based line number (0 before the first line is read).
+    pub fn line(&self) -> usize {
+        self.line
+    }
+
+    pub fn into_inner(self) -> R {
+        self.reader
+    }
+
+    fn next_line(&mut self) -> Option<Result<Event, AamlError>> {
+        loop {
+            self.buf.clear();
+            let read = match self.reader.read_line(&mut self.buf) {
+                Ok(0) => return None,
+                Ok(n) => n,
+                Err(e) => return Some(Err(e.into())),
+            };
+
+            self.line += 1;
+            let raw = self.buf.trim_end_matches(['\n', '\r']);
+            let span = Span { line: self.line, start: self.offset, end: self.offset + raw.len() };
+            self.offset += read;
+
+            let (code, comment) = lexer::split_comment(raw);
+            let comment = comment.map(|text| Event::Comment { text: text.trim().to_string(), span });
+
+            let event = match lexer::lex_line(code) {
+                Line::Empty => match comment {
+                    Some(c) => return Some(Ok(c)),
+                    None => continue,
+                },
+                Line::Assignment { key, value } => Event::Assignment {
+                    key: key.to_string(),
+                    value: value.to_string(),
+                    span,
+                },
+                Line::Directive(body) => {
+                    let (name, args) = lexer::split_directive(body);
+                    if name.is_empty() {
+                        return Some(Err(AamlError::ParseError {
+                            line: self.line,
+                            content: body.to_string(),
+                            details: "Empty directive".to_string(),
+                        }));
+                    }
+                    Event::Directive { name: name.to_string(), args: args.to_string(), span }
+                }
+                Line::BlockEnd => Event::BlockEnd { span },
+                Line::Invalid { content, details } => {
+                    return Some(Err(AamlError::ParseError {
+                        line: self.line,
+                        content: content.to_string(),
+                        details: details.to_string(),
+                    }));
+                }
+            };
+
+            self.pending = comment;
+            return Some(Ok(event));
+        }
+    }
+}
+
+impl<R: BufRead> Iterator for AamlReader<R> {
+    type Item = Result<Event, AamlError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if let Some(event) = self.pending.take() {
+            return Some(Ok(event));
+        }
+        self.next_line()
+    }
+}
@@ -0,0 +1,46 @@
+use std::fmt;
+use std::ops::Deref;
+use std::sync::Arc;
+use crate::aaml::AAML;
+
+/// A frozen, shareable view of a parsed config.
+///
+/// Produced by [`AAML::freeze`]. Cloning only bumps a reference count, and the snapshot is
+/// `Send + Sync`, so one parsed config can be handed to every thread or async task. All
+/// read APIs of [`AAML`] are available through `Deref`; nothing can modify it.
+#[derive(Clone)]
+pub struct AamlSnapshot {
+    inner: Arc<AAML>,
+}
+
+impl AamlSnapshot {
+    /// Number of live handles to this snapshot.
+    pub fn handle_count(this: &Self) -> usize {
+        Arc::strong_count(&this.inner)
+    }
+
+    /// Returns `true` if both handles point to the same frozen config.
+    pub fn ptr_eq(this: &Self, other: &Self) -> bool {
+        Arc::ptr_eq(&this.inner, &other.inner)
+    }
+}
+
+impl From<AAML> for AamlSnapshot {
+    fn from(aaml: AAML) -> Self {
+        AamlSnapshot { inner: Arc::new(aaml) }
+    }
+}
+
+impl Deref for AamlSnapshot {
+    type Target = AAML;
+
+    fn deref(&self) -> &Self::Target {
+        &self.inner
+    }
+}
+
+impl fmt::Debug for AamlSnapshot {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("AamlSnapshot").field(&*self.inner).finish()
+    }
+}
@@ -0,0 +1,193 @@
+use std::hash::BuildHasher;
+use hashbrown::HashTable;
+use crate::error::AamlError;
+use crate::store::{Entry, Hasher};
+
+/// Below this buffer size garbage is never worth compacting away.
+const COMPACT_MIN_BYTES: usize = 64 * 1024;
+
+/// Location of a string inside the arena buffer.
+#[derive(Clone, Copy)]
+struct Sym {
+    start: u32,
+    len: u32,
+}
+
+struct Slot {
+    key: Sym,
+    value: Sym,
+    seq: u64,
+}
+
+/// Arena storage: keys and values are appended to one `buf` and the table holds
+/// 8-byte `Sym`s into it instead of owning a heap allocation per string.
+///
+/// A value that names an existing key (an alias) points at that key's bytes rather than
+/// being copied. Overwritten and removed strings stay in the buffer until at least half of
+/// it is garbage, at which point it is compacted. The buffer is limited to 4 GiB.
+pub(crate) struct Store {
+    buf: String,
+    map: HashTable<Slot>,
+    hasher: Hasher,
+    /// Total length of the keys and values in the table, an upper bound of the bytes
+    /// still referenced (shared bytes are counted twice).
+    live: usize,
+}
+
+fn resolve(buf: &str, sym: Sym) -> &str {
+    let start = sym.start as usize;
+    &buf[start..start + sym.len as usize]
+}
+
+fn hash(hasher: &Hasher, s: &str) -> u64 {
+    BuildHasher::hash_one(hasher, s)
+}
+
+impl Store {
+    pub fn with_capacity(capacity: usize) -> Self {
+        Store {
+            buf: String::new(),
+            map: HashTable::with_capacity(capacity),
+            hasher: Hasher::default(),
+            live: 0,
+        }
+    }
+
+    pub fn reserve(&mut self, entries: usize, bytes: usize) {
+        let Store { buf, map, hasher, .. } = self;
+        buf.reserve(bytes);
+        map.reserve(entries, |slot| hash(hasher, resolve(buf, slot.key)));
+    }
+
+    pub fn len(&self) -> usize {
+        self.map.len()
+    }
+
+    pub fn get(&self, key: &str) -> Option<Entry<'_>> {
+        self.find(hash(&self.hasher, key), key).map(|slot| self.entry(slot))
+    }
+
+    /// Fails only when the buffer would outgrow 4 GiB even after compaction.
+    pub fn insert(&mut self, key: &str, value: &str, seq: u64) -> Result<(), AamlError> {
+        self.ensure_room(key.len() + value.len())?;
+
+        let value_sym = match self.find(hash(&self.hasher, value), value) {
+            Some(alias) => alias.key,
+            None => self.push(value),
+        };
+
+        let key_hash = hash(&self.hasher, key);
+        let Store { buf, map, live, .. } = self;
+        if let Some(slot) = map.find_mut(key_hash, |slot| resolve(buf, slot.key) == key) {
+            *live = *live + value.len() - slot.value.len as usize;
+            slot.value = value_sym;
+            slot.seq = seq;
+            self.maybe_compact();
+            return Ok(());
+        }
+
+        let key_sym = if key == value { value_sym } else { self.push(key) };
+        let Store { buf, map, hasher, live } = self;
+        *live += key.len() + value.len();
+        map.insert_unique(key_hash, Slot { key: key_sym, value: value_sym, seq }, |slot| {
+            hash(hasher, resolve(buf, slot.key))
+        });
+        self.maybe_compact();
+        Ok(())
+    }
+
+    /// Removes `key` from the table; its bytes stay in the buffer until the next compaction.
+    pub fn remove(&mut self, key: &str) -> bool {
+        let key_hash = hash(&self.hasher, key);
+        let Store { buf, map, live, .. } = self;
+        match map.find_entry(key_hash, |slot| resolve(buf, slot.key) == key) {
+            Ok(entry) => {
+                let (slot, _) = entry.remove();
+                *live -= (slot.key.len + slot.value.len) as usize;
+                self.maybe_compact();
+                true
+            }
+            Err(_) => false,
+        }
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = Entry<'_>> {
+        self.map.iter().map(|slot| self.entry(slot))
+    }
+
+    fn find(&self, hash: u64, key: &str) -> Option<&Slot> {
+        self.map.find(hash, |slot| resolve(&self.buf, slot.key) == key)
+    }
+
+    fn entry(&self, slot: &Slot) -> Entry<'_> {
+        Entry {
+            key: resolve(&self.buf, slot.key),
+            value: resolve(&self.buf, slot.value),
+            seq: slot.seq,
+        }
+    }
+
+    /// Appends `s`; [`ensure_room`](Self::ensure_room) must have made space for it.
+    fn push(&mut self, s: &str) -> Sym {
+        let sym = Sym { start: self.buf.len() as u32, len: s.len() as u32 };
+        self.buf.push_str(s);
+        sym
+    }
+
+    /// Makes sure `extra` more bytes stay addressable by a `Sym`, compacting if that helps.
+    fn ensure_room(&mut self, extra: usize) -> Result<(), AamlError> {
+        let fits = |len: usize| len.checked_add(extra).is_some_and(|end| end <= u32::MAX as usize);
+        if !fits(self.buf.len()) && self.live < self.buf.len() {
+            self.compact();
+        }
+        if fits(self.buf.len()) {
+            Ok(())
+        } else {
+            Err(AamlError::InvalidValue(format!(
+                "config exceeds the 4 GiB limit of arena storage ({} more bytes needed)", extra
+            )))
+        }
+    }
+
+    fn maybe_compact(&mut self) {
+        if self.buf.len() > COMPACT_MIN_BYTES && self.buf.len() > 2 * self.live {
+            self.compact();
+        }
+    }
+
+    /// Copies the referenced strings into a fresh buffer, dropping overwritten and removed
+    /// ones. Keys are copied first so that aliases can share them again.
+    fn compact(&mut self) {
+        let Store { buf, map, hasher, live } = self;
+        let mut fresh = String::with_capacity(*live);
+        fn copy(fresh: &mut String, s: &str) -> Sym {
+            let sym = Sym { start: fresh.len() as u32, len: s.len() as u32 };
+            fresh.push_str(s);
+            sym
+        }
+
+        let old_values: Vec<Sym> = map.iter().map(|slot| slot.value).collect();
+        for slot in map.iter_mut() {
+            slot.key = copy(&mut fresh, resolve(buf, slot.key));
+        }
+
+        let values: Vec<Sym> = map
+            .iter()
+            .zip(&old_values)
+            .map(|(slot, &old)| {
+                let value = resolve(buf, old);
+                if value == resolve(&fresh, slot.key) {
+                    return slot.key;
+                }
+                match map.find(hash(hasher, value), |other| resolve(&fresh, other.key) == value) {
+                    Some(alias) => alias.key,
+                    None => copy(&mut fresh, value),
+                }
+            })
+            .collect();
+        for (slot, value) in map.iter_mut().zip(values) {
+            slot.value = value;
+        }
+        *buf = fresh;
+    }
+}
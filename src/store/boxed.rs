@@ -0,0 +1,52 @@
+use std::collections::HashMap;
+use crate::error::AamlError;
+use crate::store::{Entry, Hasher};
+
+struct Slot {
+    value: Box<str>,
+    seq: u64,
+}
+
+pub(crate) struct Store {
+    map: HashMap<Box<str>, Slot, Hasher>,
+}
+
+impl Store {
+    pub fn with_capacity(capacity: usize) -> Self {
+        Store {
+            map: HashMap::with_capacity_and_hasher(capacity, Hasher::default()),
+        }
+    }
+
+    /// Makes room for `entries` more assignments; `_bytes` is only used by the arena backend.
+    pub fn reserve(&mut self, entries: usize, _bytes: usize) {
+        self.map.reserve(entries);
+    }
+
+    pub fn len(&self) -> usize {
+        self.map.len()
+    }
+
+    pub fn get(&self, key: &str) -> Option<Entry<'_>> {
+        self.map.get_key_value(key).map(|(k, slot)| Entry { key: k, value: &slot.value, seq: slot.seq })
+    }
+
+    /// Never fails; the `Result` matches the arena backend, which has a size limit.
+    pub fn insert(&mut self, key: &str, value: &str, seq: u64) -> Result<(), AamlError> {
+        if let Some(slot) = self.map.get_mut(key) {
+            slot.value = Box::from(value);
+            slot.seq = seq;
+        } else {
+            self.map.insert(Box::from(key), Slot { value: Box::from(value), seq });
+        }
+        Ok(())
+    }
+
+    pub fn remove(&mut self, key: &str) -> bool {
+        self.map.remove(key).is_some()
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = Entry<'_>> {
+        self.map.iter().map(|(k, slot)| Entry { key: k, value: &slot.value, seq: slot.seq })
+    }
+}
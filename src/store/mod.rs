@@ -0,0 +1,30 @@
+//! Backing storage for the key-value map of an `AAML` instance.
+//!
+//! By default every key and value is its own `Box<str>`. With the `arena` feature the
+//! strings live in one contiguous buffer addressed by offsets, and a value that names an
+//! existing key (an alias) shares that key's bytes.
+
+#[cfg(not(feature = "arena"))]
+mod boxed;
+#[cfg(feature = "arena")]
+mod arena;
+
+#[cfg(not(feature = "arena"))]
+pub(crate) use boxed::Store;
+#[cfg(feature = "arena")]
+pub(crate) use arena::Store;
+
+#[cfg(feature = "perf-hash")]
+pub(crate) type Hasher = ahash::RandomState;
+
+#[cfg(not(feature = "perf-hash"))]
+pub(crate) type Hasher = std::collections::hash_map::RandomState;
+
+/// A borrowed view of one stored assignment.
+#[derive(Clone, Copy, Debug)]
+pub(crate) struct Entry<'a> {
+    pub key: &'a str,
+    pub value: &'a str,
+    /// Position of the assignment that produced the value (see `AAML::find_keys`).
+    pub seq: u64,
+}
@@ -0,0 +1,185 @@
+#[cfg(test)]
+mod tests {
+    use crate::aaml::AAML;
+    use crate::builder::AAMBuilder;
+    use crate::compiled::FORMAT_VERSION;
+    use crate::error::AamlError;
+    use crate::options::ParseOptions;
+    use std::fs;
+    use std::path::Path;
+
+    #[test]
+    fn test_compile_roundtrip() {
+        let root = "test_compiled_root.aam";
+        let sub = "test_compiled_sub.aam";
+
+        let mut b = AAMBuilder::new();
+        b.add_line("db_host", "10.0.0.1");
+        b.add_line("mode", "prod");
+        b.to_file(sub).unwrap();
+
+        let mut b = AAMBuilder::new();
+        b.add_raw("@type port = i32");
+        b.add_raw("@schema Server { host: string, port: port }");
+        b.add_line("web", "prod");
+        b.add_raw(&format!("@import {sub}"));
+        b.add_line("port", "8080");
+        b.add_line("alias", "db_host");
+        b.to_file(root).unwrap();
+
+        let blob = AAML::compile(root);
+        let _ = fs::remove_file(root);
+        let _ = fs::remove_file(sub);
+        let blob = blob.expect("Should compile");
+
+        let aaml = AAML::from_compiled(&blob).expect("Should load compiled");
+        assert_eq!(aaml.find_obj("port").unwrap().as_str(), "8080");
+        assert_eq!(aaml.find_deep_ref("alias"), Some("10.0.0.1"));
+        assert_eq!(aaml.find_keys_ref("prod"), vec!["web", "mode"]);
+        assert!(aaml.validate_value("port", "80").is_ok());
+        assert!(aaml.validate_value("port", "eighty").is_err());
+        assert_eq!(aaml.get_schema("Server").unwrap().fields.get("port").unwrap(), "port");
+
+        let loc = aaml.definition_location("port").expect("Span for 'port'");
+        assert_eq!((loc.file, loc.line), (Some(Path::new(root)), 5));
+        let loc = aaml.definition_location("db_host").expect("Span for 'db_host'");
+        assert_eq!((loc.file, loc.line), (Some(Path::new(sub)), 1));
+
+        assert_eq!(aaml.to_compiled(), blob);
+    }
+
+    #[test]
+    fn test_compiled_rejects_corruption() {
+        let blob = AAML::parse("a = 1\nb = 2").unwrap().to_compiled();
+        assert!(AAML::from_compiled(&blob).is_ok());
+
+        let mut flipped = blob.clone();
+        *flipped.last_mut().unwrap() ^= 0xff;
+        assert!(matches!(AAML::from_compiled(&flipped), Err(AamlError::InvalidCompiled(msg)) if msg.contains("checksum")));
+
+        let mut versioned = blob.clone();
+        versioned[4..6].copy_from_slice(&(FORMAT_VERSION + 1).to_le_bytes());
+        assert!(matches!(AAML::from_compiled(&versioned), Err(AamlError::InvalidCompiled(msg)) if msg.contains("version")));
+
+        assert!(AAML::from_compiled(&blob[..blob.len() - 3]).is_err());
+        assert!(AAML::from_compiled(b"a = 1").is_err());
+    }
+
+    #[test]
+    fn test_compiled_staleness() {
+        let root = "test_compiled_stale.aam";
+        fs::write(root, "key = old").unwrap();
+        let blob = AAML::compile(root).expect("Should compile");
+        let fresh = AAML::is_compiled_fresh(&blob);
+
+        fs::write(root, "key = newer").unwrap();
+        let stale = AAML::is_compiled_fresh(&blob);
+        let _ = fs::remove_file(root);
+
+        assert!(fresh.unwrap());
+        assert!(!stale.unwrap());
+        assert!(!AAML::is_compiled_fresh(&blob).unwrap());
+    }
+
+    #[test]
+    fn test_load_cached_falls_back_to_text() {
+        let root = "test_compiled_cached.aam";
+        let cache = "test_compiled_cached.aamc";
+        fs::write(root, "key = first").unwrap();
+
+        let first = AAML::load_cached(root, cache);
+        let cached = fs::read(cache);
+        let second = AAML::load_cached(root, cache);
+
+        fs::write(root, "key = second_version").unwrap();
+        let third = AAML::load_cached(root, cache);
+
+        let _ = fs::remove_file(root);
+        let _ = fs::remove_file(cache);
+
+        assert_eq!(first.unwrap().find_obj_ref("key"), Some("first"));
+        assert!(cached.is_ok_and(|bytes| AAML::from_compiled(&bytes).is_ok()));
+        assert_eq!(second.unwrap().find_obj_ref("key"), Some("first"));
+        assert_eq!(third.unwrap().find_obj_ref("key"), Some("second_version"));
+    }
+
+    #[test]
+    fn test_load_cached_sees_new_glob_matches_and_optional_files() {
+        let dir = "test_compiled_glob_dir";
+        let root = "test_compiled_glob.aam";
+        let cache = "test_compiled_glob.aamc";
+        let _ = fs::remove_dir_all(dir);
+        fs::create_dir_all(dir).unwrap();
+        fs::write(format!("{dir}/a.aam"), "a = 1").unwrap();
+        fs::write(root, format!("@import {dir}/*.aam\n@import? {dir}/extra.txt")).unwrap();
+
+        let first = AAML::load_cached(root, cache);
+        fs::write(format!("{dir}/b.aam"), "b = 2").unwrap();
+        let after_glob = fs::read(cache).map(|bytes| AAML::is_compiled_fresh(&bytes));
+        let second = AAML::load_cached(root, cache);
+        fs::write(format!("{dir}/extra.txt"), "c = 3").unwrap();
+        let after_optional = fs::read(cache).map(|bytes| AAML::is_compiled_fresh(&bytes));
+        let third = AAML::load_cached(root, cache);
+
+        let _ = fs::remove_dir_all(dir);
+        let _ = fs::remove_file(root);
+        let _ = fs::remove_file(cache);
+
+        assert_eq!(first.unwrap().find_obj_ref("b"), None);
+        assert!(matches!(after_glob, Ok(Ok(false))));
+        assert_eq!(second.unwrap().find_obj_ref("b"), Some("2"));
+        assert!(matches!(after_optional, Ok(Ok(false))));
+        let third = third.unwrap();
+        assert_eq!(third.find_obj_ref("b"), Some("2"));
+        assert_eq!(third.find_obj_ref("c"), Some("3"));
+    }
+
+    #[test]
+    fn test_load_cached_checks_options() {
+        let root = "test_compiled_context.aam";
+        let cache = "test_compiled_context.aamc";
+        fs::write(root, "mode = default\n@profile staging {\nmode = staging\n}").unwrap();
+
+        let plain = AAML::load_cached(root, cache);
+        let staging = AAML::load_cached_with_options(root, cache, ParseOptions::new().profile("staging"));
+        let cached = AAML::load_cached_with_options(root, cache, ParseOptions::new().profile("staging"));
+        let unprofiled = AAML::load_cached(root, cache);
+
+        let _ = fs::remove_file(root);
+        let _ = fs::remove_file(cache);
+
+        assert_eq!(plain.unwrap().find_obj_ref("mode"), Some("default"));
+        assert_eq!(staging.unwrap().find_obj_ref("mode"), Some("staging"));
+        let cached = cached.unwrap();
+        assert_eq!(cached.find_obj_ref("mode"), Some("staging"));
+        assert_eq!(cached.options().profiles(), ["staging"]);
+        assert_eq!(unprofiled.unwrap().find_obj_ref("mode"), Some("default"));
+    }
+
+    #[test]
+    fn test_compile_namespaced_import_keeps_locations() {
+        let root = "test_compiled_ns_root.aam";
+        let sub = "test_compiled_ns_sub.aam";
+
+        let mut b = AAMBuilder::new();
+        b.add_raw("@type port = i32");
+        b.add_line("host", "db.local");
+        b.add_line("port", "5432");
+        b.to_file(sub).unwrap();
+
+        let mut b = AAMBuilder::new();
+        b.add_line("name", "app");
+        b.add_raw(&format!("@import {sub} as db"));
+        b.to_file(root).unwrap();
+
+        let blob = AAML::compile(root);
+        let _ = fs::remove_file(root);
+        let _ = fs::remove_file(sub);
+
+        let aaml = AAML::from_compiled(&blob.unwrap()).unwrap();
+        let loc = aaml.definition_location("db.port").expect("Span for 'db.port'");
+        assert_eq!(loc.file, Some(Path::new(sub)));
+        assert_eq!(loc.line, 3);
+        assert!(aaml.validate_value("db.port", "x").is_err());
+    }
+}
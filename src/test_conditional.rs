@@ -0,0 +1,131 @@
+#[cfg(test)]
+mod tests {
+    use crate::aaml::AAML;
+    use crate::builder::AAMBuilder;
+    use crate::error::AamlError;
+    use crate::options::ParseOptions;
+    use std::fs;
+    use std::io::Cursor;
+
+    const ENVIRONMENTS: &str = r#"
+env = staging
+@if env == "prod"
+    host = prod.example.com
+@elif env == "staging" && !debug
+    host = staging.example.com
+@else
+    host = localhost
+@endif
+"#;
+
+    #[test]
+    fn test_if_elif_else_against_keys() {
+        let parser = AAML::parse(ENVIRONMENTS).unwrap();
+        assert_eq!(parser.find_obj("host").unwrap().as_str(), "staging.example.com");
+
+        let parser = AAML::parse(&ENVIRONMENTS.replace("env = staging", "env = qa")).unwrap();
+        assert_eq!(parser.find_obj("host").unwrap().as_str(), "localhost");
+    }
+
+    #[test]
+    fn test_options_take_precedence_over_keys() {
+        let options = ParseOptions::new().var("env", "prod");
+        let parser = AAML::parse_with_options(ENVIRONMENTS, options).unwrap();
+        assert_eq!(parser.find_obj("host").unwrap().as_str(), "prod.example.com");
+
+        let options = ParseOptions::new().var("debug", "true");
+        let parser = AAML::parse_with_options(ENVIRONMENTS, options).unwrap();
+        assert_eq!(parser.find_obj("host").unwrap().as_str(), "localhost");
+        assert_eq!(parser.options().get_var("debug"), Some("true"));
+    }
+
+    #[test]
+    fn test_nested_blocks_and_skipped_directives() {
+        let content = r#"
+a = 1
+@if a == 1
+    @if b
+        inner = wrong
+    @else
+        inner = right
+    @endif
+@else
+    @if undefined_call(
+    @import does_not_exist.aam
+    outer = wrong
+    @endif
+@endif
+"#;
+        let parser = AAML::parse(content).unwrap();
+        assert_eq!(parser.find_obj("inner").unwrap().as_str(), "right");
+        assert!(parser.find_obj("outer").is_none());
+    }
+
+    #[test]
+    fn test_conditional_nesting_errors() {
+        let cases = [
+            ("@endif", 1, "@endif without @if"),
+            ("x = 1\n@else", 2, "@else without @if"),
+            ("@if 1\n@else\n@elif 1\n@endif", 3, "@elif after @else"),
+            ("@if 1\n@else\n@else\n@endif", 3, "Duplicate @else"),
+            ("@if 1\n@if 0\n@endif", 1, "Unterminated @if"),
+            ("@if\n@endif", 1, "Missing condition"),
+            ("@if a == b == c\n@endif", 1, "Invalid operand"),
+        ];
+
+        for (content, expected_line, expected) in cases {
+            match AAML::parse(content) {
+                Err(AamlError::ParseError { line, details, .. }) => {
+                    assert_eq!(line, expected_line, "{content}");
+                    assert!(details.contains(expected), "{content}: {details}");
+                }
+                other => panic!("Expected ParseError for {content:?}, got {other:?}"),
+            }
+        }
+    }
+
+    #[test]
+    fn test_blocks_do_not_span_imports() {
+        let file = "conditional_unclosed.aam";
+        let mut b = AAMBuilder::new();
+        b.add_raw("@if 1");
+        b.add_line("k", "v");
+        b.to_file(file).unwrap();
+
+        let result = AAML::parse(&format!("@import {file}\n@endif"));
+        let _ = fs::remove_file(file);
+
+        assert!(matches!(result, Err(AamlError::ParseError { line: 1, .. })));
+    }
+
+    #[test]
+    fn test_imports_see_options() {
+        let file = "conditional_options_sub.aam";
+        let mut b = AAMBuilder::new();
+        b.add_raw("@if tier == \"gold\"");
+        b.add_line("limit", "1000");
+        b.add_raw("@endif");
+        b.to_file(file).unwrap();
+
+        let main = "conditional_options_main.aam";
+        let mut b = AAMBuilder::new();
+        b.add_raw(&format!("@import {file}"));
+        b.to_file(main).unwrap();
+
+        let result = AAML::load_with_options(main, ParseOptions::new().var("tier", "gold"));
+        let _ = fs::remove_file(file);
+        let _ = fs::remove_file(main);
+
+        assert_eq!(result.unwrap().find_obj("limit").unwrap().as_str(), "1000");
+    }
+
+    #[test]
+    fn test_reader_applies_conditionals() {
+        let mut parser = AAML::new();
+        parser.merge_reader(Cursor::new(ENVIRONMENTS)).unwrap();
+        assert_eq!(parser.find_obj("host").unwrap().as_str(), "staging.example.com");
+
+        let mut parser = AAML::new();
+        assert!(parser.merge_reader(Cursor::new("@if 1\nk = v\n")).is_err());
+    }
+}
@@ -0,0 +1,151 @@
+#[cfg(all(test, any(feature = "json", feature = "toml", feature = "yaml")))]
+mod tests {
+    use crate::aaml::AAML;
+    #[cfg(any(feature = "json", feature = "toml"))]
+    use crate::error::AamlError;
+
+    fn typed_config() -> AAML {
+        AAML::parse(
+            "@type port = i32\n\
+             @type ratio = f64\n\
+             @type debug = bool\n\
+             @type weights = f64\n\
+             server.host = localhost\n\
+             server.port = 8080\n\
+             ratio = 0.5\n\
+             debug = 1\n\
+             weights = 1, 2.5\n\
+             version = 007\n\
+             name = \"demo\"",
+        )
+        .unwrap()
+    }
+
+    #[cfg(feature = "json")]
+    #[test]
+    fn test_from_json_flattens_objects() {
+        let aaml = AAML::from_json(
+            r#"{"db": {"host": "h", "port": 5432, "replicas": [{"host": "r1"}, {"host": "r2"}]},
+                "tags": ["a", "b"], "ratio": 1.5, "on": true, "missing": null}"#,
+        )
+        .unwrap();
+
+        assert_eq!(aaml.find_obj("db.host").unwrap().as_str(), "h");
+        assert_eq!(aaml.find_obj("db.port").unwrap().as_str(), "5432");
+        assert_eq!(aaml.find_obj("db.replicas.1.host").unwrap().as_str(), "r2");
+        assert_eq!(aaml.find_obj("tags").unwrap().as_str(), "[a, b]");
+        assert_eq!(aaml.find_obj("ratio").unwrap().as_str(), "1.5");
+        assert_eq!(aaml.find_obj("on").unwrap().as_str(), "true");
+        assert_eq!(aaml.find_obj("missing").unwrap().as_str(), "");
+    }
+
+    #[cfg(feature = "json")]
+    #[test]
+    fn test_to_json_uses_registered_types() {
+        let json: serde_json::Value = serde_json::from_str(&typed_config().to_json().unwrap()).unwrap();
+
+        assert_eq!(json["server"]["host"], "localhost");
+        assert_eq!(json["server"]["port"], 8080);
+        assert_eq!(json["ratio"], 0.5);
+        assert_eq!(json["debug"], true);
+        assert_eq!(json["weights"], serde_json::json!([1.0, 2.5]));
+        assert_eq!(json["version"], "007");
+        assert_eq!(json["name"], "demo");
+    }
+
+    #[cfg(feature = "json")]
+    #[test]
+    fn test_json_roundtrip_keeps_arrays_and_order() {
+        let source = r#"{"z":1,"a":{"list":[{"x":1},{"x":2}]},"ports":[80,443]}"#;
+        let json = AAML::from_json(source).unwrap().to_json().unwrap();
+        let compact: serde_json::Value = serde_json::from_str(&json).unwrap();
+
+        assert_eq!(compact.to_string(), r#"{"z":1,"a":{"list":[{"x":1},{"x":2}]},"ports":[80,443]}"#);
+    }
+
+    #[cfg(feature = "json")]
+    #[test]
+    fn test_json_roundtrip_keeps_strings_that_look_typed() {
+        let source = serde_json::json!({
+            "flag": "true", "count": "1", "ratio": "0.5", "empty": "", "quoted": "\"x\"", "pair": "1, 2",
+            "text": "a, b", "big": 1e300, "none": null,
+            "tags": ["a, b", "c"], "one": ["x"], "no_items": [], "mixed": [1, "1", true, "true", null, "null", "", " pad", "[x]", "say \"hi\""]
+        });
+        let aaml = AAML::from_json(&source.to_string()).unwrap();
+        assert_eq!(aaml.find_obj_ref("flag"), Some("true"));
+        assert_eq!(aaml.find_obj_ref("quoted"), Some("\"x\""));
+        assert_eq!(aaml.get_as::<String>("count").unwrap(), "1");
+        assert!(aaml.get_type("flag").is_some());
+        assert!(aaml.get_type("text").is_none());
+        assert_eq!(aaml.find_obj("text").unwrap().as_str(), "a, b");
+        assert_eq!(aaml.find_obj("tags").unwrap().as_str(), "[\"a, b\", c]");
+
+        let back: serde_json::Value = serde_json::from_str(&aaml.to_json().unwrap()).unwrap();
+        assert_eq!(back, source);
+    }
+
+    #[cfg(feature = "json")]
+    #[test]
+    fn test_json_string_types_stay_with_their_key() {
+        let source = serde_json::json!({"a": "1", "x": {"a": 1}, "c": {"d": "true"}, "e": {"d": false}});
+        let aaml = AAML::from_json(&source.to_string()).unwrap();
+        assert_eq!(aaml.find_obj_ref("a"), AAML::parse("a = \"1\"").unwrap().find_obj_ref("a"));
+
+        let back: serde_json::Value = serde_json::from_str(&aaml.to_json().unwrap()).unwrap();
+        assert_eq!(back, source);
+        let restored = AAML::from_compiled(&aaml.to_compiled()).unwrap();
+        let back: serde_json::Value = serde_json::from_str(&restored.to_json().unwrap()).unwrap();
+        assert_eq!(back, source);
+    }
+
+    #[cfg(feature = "json")]
+    #[test]
+    fn test_to_json_reads_bracketed_lists() {
+        let aaml = AAML::parse("@type weights = f64\nweights = [1, 2.5]\nnames = [a, \"b, c\"]\nmatrix = [[1, 0], [0, 1]]").unwrap();
+        let json: serde_json::Value = serde_json::from_str(&aaml.to_json().unwrap()).unwrap();
+
+        assert_eq!(json["weights"], serde_json::json!([1.0, 2.5]));
+        assert_eq!(json["names"], serde_json::json!(["a", "b, c"]));
+        assert_eq!(json["matrix"], "[[1, 0], [0, 1]]");
+    }
+
+    #[cfg(feature = "json")]
+    #[test]
+    fn test_json_errors() {
+        assert!(matches!(AAML::from_json("{\n\"a\": }"), Err(AamlError::ParseError { line: 2, .. })));
+
+        let conflicting = AAML::parse("a = 1\na.b = 2").unwrap();
+        assert!(matches!(conflicting.to_json(), Err(AamlError::InvalidValue(_))));
+    }
+
+    #[cfg(feature = "toml")]
+    #[test]
+    fn test_toml_roundtrip() {
+        let aaml = AAML::from_toml("title = \"x\"\n[server]\nport = 80\nhosts = [\"a\", \"b\"]\n").unwrap();
+        assert_eq!(aaml.find_obj("server.port").unwrap().as_str(), "80");
+        assert_eq!(aaml.find_obj("server.hosts").unwrap().as_str(), "[a, b]");
+
+        let table: toml::Table = typed_config().to_toml().unwrap().parse().unwrap();
+        assert_eq!(table["server"]["port"].as_integer(), Some(8080));
+        assert_eq!(table["debug"].as_bool(), Some(true));
+        assert_eq!(table["version"].as_str(), Some("007"));
+
+        assert!(matches!(AAML::from_toml("a = 1\nb = \n"), Err(AamlError::ParseError { line: 2, .. })));
+    }
+
+    #[cfg(feature = "yaml")]
+    #[test]
+    fn test_yaml_roundtrip() {
+        let aaml = AAML::from_yaml("server:\n  port: 80\n  hosts: [a, b]\nratio: 0.25\n").unwrap();
+        assert_eq!(aaml.find_obj("server.port").unwrap().as_str(), "80");
+        assert_eq!(aaml.find_obj("server.hosts").unwrap().as_str(), "[a, b]");
+        assert_eq!(aaml.find_obj("ratio").unwrap().as_str(), "0.25");
+
+        let yaml = typed_config().to_yaml().unwrap();
+        let back = AAML::from_yaml(&yaml).unwrap();
+        assert_eq!(back.find_obj("server.port").unwrap().as_str(), "8080");
+        assert_eq!(back.find_obj("weights").unwrap().as_str(), "[1.0, 2.5]");
+        assert!(yaml.contains("port: 8080"));
+        assert!(yaml.contains("debug: true"));
+    }
+}
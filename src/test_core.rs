@@ -1,6 +1,8 @@
 #[cfg(test)]
 mod tests {
     use crate::aaml::AAML;
+    use crate::deep_result::DeepResult;
+    use crate::error::AamlError;
     use crate::found_value::FoundValue;
 
     const TEST_CONFIG: &str = "
@@ -36,7 +38,7 @@ mod tests {
 
     #[test]
     fn test_display_trait() {
-        let res = FoundValue::new(&*"hello".to_string());
+        let res = FoundValue::new("hello");
         let formatted = format!("{}", res);
         assert_eq!(formatted, "hello");
     }
@@ -185,6 +187,17 @@ mod tests {
         assert_eq!(aaml1.find_obj("b").unwrap().as_str(), "3");
     }
 
+    #[test]
+    fn test_fallible_merge() {
+        let mut aaml1 = AAML::parse("a = 1
+@type port = i32").unwrap();
+        aaml1.merge(AAML::parse("a = 2
+b = 3").unwrap()).unwrap();
+        assert_eq!(aaml1.find_obj("a").unwrap().as_str(), "2");
+        assert_eq!(aaml1.find_obj("b").unwrap().as_str(), "3");
+        assert!(aaml1.get_type("port").is_some());
+    }
+
     #[test]
     fn test_reverse_lookup() {
         let content = "username = admin";
@@ -222,4 +235,192 @@ mod tests {
         assert!(res.is_some());
         assert_eq!(res.unwrap().as_str(), "c");
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_find_keys_returns_all_matches() {
+        let parser = AAML::parse("a = shared\nb = shared\nc = other").expect("Parsed");
+        let keys: Vec<String> = parser.find_keys("shared").iter().map(|k| k.to_string()).collect();
+        assert_eq!(keys, vec!["a", "b"]);
+        assert!(parser.find_keys("missing").is_empty());
+    }
+
+    #[test]
+    fn test_reverse_index_lookup() {
+        let mut aaml = AAML::new();
+        aaml.enable_reverse_index();
+        aaml.merge_content("username = admin\nrole = admin").expect("Parsed");
+
+        assert!(aaml.has_reverse_index());
+        assert_eq!(aaml.find_obj("admin").unwrap().as_str(), "username");
+        assert_eq!(aaml.find_keys("admin").len(), 2);
+    }
+
+    #[test]
+    fn test_reverse_index_tracks_overwrites() {
+        let mut aaml = AAML::parse("a = 1\nb = 1").expect("Parsed");
+        aaml.enable_reverse_index();
+        aaml.merge_content("a = 2").expect("Merge failed");
+
+        assert_eq!(aaml.find_key("1").unwrap().as_str(), "b");
+        assert_eq!(aaml.find_key("2").unwrap().as_str(), "a");
+
+        aaml.merge_content("b = 2").expect("Merge failed");
+        assert!(aaml.find_key("1").is_none());
+        assert_eq!(aaml.find_keys("2").len(), 2);
+    }
+
+    #[test]
+    fn test_reverse_index_with_add_assign() {
+        let mut aaml1 = AAML::parse("a = x").unwrap();
+        aaml1.enable_reverse_index();
+        aaml1 += AAML::parse("a = y\nb = x").unwrap();
+
+        assert_eq!(aaml1.find_key("x").unwrap().as_str(), "b");
+        assert_eq!(aaml1.find_key("y").unwrap().as_str(), "a");
+    }
+
+    #[test]
+    fn test_find_keys_definition_order() {
+        let content = "z = prod\nm = prod\na = prod\nm = dev\nm = prod";
+        let mut parser = AAML::parse(content).expect("Parsed");
+
+        let expected = vec!["z", "a", "m"];
+        let keys: Vec<String> = parser.find_keys("prod").iter().map(|k| k.to_string()).collect();
+        assert_eq!(keys, expected);
+        assert_eq!(parser.find_obj("prod").unwrap().as_str(), "z");
+
+        parser.enable_reverse_index();
+        let keys: Vec<String> = parser.find_keys("prod").iter().map(|k| k.to_string()).collect();
+        assert_eq!(keys, expected);
+    }
+
+    #[test]
+    fn test_find_obj_strict_ambiguous() {
+        let parser = AAML::parse("app = production\nworker = production\ndebug = false").expect("Parsed");
+
+        match parser.find_obj_strict("production") {
+            Err(AamlError::AmbiguousLookup { value, keys }) => {
+                assert_eq!(value, "production");
+                assert_eq!(keys, vec!["app", "worker"]);
+            }
+            other => panic!("Expected ambiguity error, got {:?}", other),
+        }
+
+        assert_eq!(parser.find_obj_strict("false").unwrap().as_str(), "debug");
+        assert_eq!(parser.find_obj_strict("app").unwrap().as_str(), "production");
+        assert!(matches!(parser.find_obj_strict("missing"), Err(AamlError::NotFound(_))));
+    }
+
+    #[test]
+    fn test_find_deep_trace_resolved() {
+        let parser = AAML::parse("service = executable\nexecutable = root\nroot = /usr/bin").expect("Parsed");
+        let res = parser.find_deep_trace("service");
+
+        assert!(res.is_resolved());
+        assert_eq!(res.chain(), ["service", "executable", "root"]);
+        assert_eq!(res.value().unwrap().as_str(), "/usr/bin");
+    }
+
+    #[test]
+    fn test_find_deep_trace_cycle() {
+        let parser = AAML::parse("start=mid\nmid=end\nend=mid").expect("Parsed");
+
+        match parser.find_deep_trace("start") {
+            DeepResult::Cycle { chain, cycle } => {
+                assert_eq!(chain, vec!["start", "mid", "end"]);
+                assert_eq!(cycle, vec!["mid", "end"]);
+            }
+            other => panic!("Expected cycle, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_find_deep_trace_dangling() {
+        let parser = AAML::parse("a = b").expect("Parsed");
+        let res = parser.find_deep_trace("missing");
+        assert_eq!(res, DeepResult::Dangling { chain: vec!["missing".to_string()] });
+        assert!(res.value().is_none());
+    }
+
+    #[test]
+    fn test_detect_alias_cycles() {
+        let content = "x = loop_b\nloop_a = loop_b\nloop_b = loop_a\nself = self\nok = fine\nc1 = c2\nc2 = c3\nc3 = c1";
+        let parser = AAML::parse(content).expect("Parsed");
+
+        let cycles = parser.detect_alias_cycles();
+        assert_eq!(cycles, vec![
+            vec!["loop_a".to_string(), "loop_b".to_string()],
+            vec!["self".to_string()],
+            vec!["c1".to_string(), "c2".to_string(), "c3".to_string()],
+        ]);
+
+        assert!(AAML::parse(TEST_CONFIG.replace("loop2 = loop1", "").as_str())
+            .unwrap()
+            .detect_alias_cycles()
+            .is_empty());
+    }
+
+    #[test]
+    fn test_borrowed_lookups() {
+        let parser = AAML::parse(TEST_CONFIG).expect("Error parsing config");
+
+        let value: &str = parser.find_obj_ref("a").expect("Should find 'a'");
+        assert_eq!(value, "b");
+        assert_eq!(parser.find_obj_ref("b"), Some("a"));
+        assert_eq!(parser.find_deep_ref("c"), Some("g"));
+        assert_eq!(parser.find_key_ref("f"), Some("e"));
+        assert_eq!(parser.find_keys_ref("d"), vec!["c"]);
+        assert!(parser.find_obj_ref("unknown").is_none());
+
+        assert_eq!(parser.find_deep_ref("loop1"), parser.find_deep("loop1").as_ref().map(|v| v.as_str()));
+    }
+
+    #[test]
+    fn test_storage_aliases_and_overwrites() {
+        let content = "self = self\nbase = /var/www\npath = base\nbase = /srv\nother = path\npath = /tmp";
+        let parser = AAML::parse(content).expect("Parsed");
+
+        assert_eq!(parser.find_obj_ref("self"), Some("self"));
+        assert_eq!(parser.find_obj_ref("base"), Some("/srv"));
+        assert_eq!(parser.find_obj_ref("path"), Some("/tmp"));
+        assert_eq!(parser.find_obj_ref("other"), Some("path"));
+        assert_eq!(parser.find_deep_ref("other"), Some("/tmp"));
+        assert!(parser.find_key_ref("/var/www").is_none());
+    }
+
+    #[test]
+    fn test_large_document_last_write_wins() {
+        let mut content = String::new();
+        for i in 0..60_000 {
+            content.push_str(&format!("key_{} = first_{}\n", i % 1000, i));
+        }
+        content.push_str("@type port = i32\n");
+        content.push_str("key_7 = last\n");
+        content.push_str("broken line\n");
+
+        let err = AAML::parse(&content).unwrap_err();
+        assert!(matches!(err, AamlError::ParseError { line: 60_003, .. }));
+
+        let parser = AAML::parse(content.trim_end_matches("broken line\n")).expect("Parsed");
+        assert_eq!(parser.find_obj("key_7").unwrap().as_str(), "last");
+        assert_eq!(parser.find_obj("key_999").unwrap().as_str(), "first_59999");
+        assert!(parser.get_type("port").is_some());
+    }
+
+    #[test]
+    fn test_overwritten_values_keep_aliases_intact() {
+        let mut content = String::from("target = shared\nalias = target\nself = self\ngone = x\n@unset gone\n");
+        let filler = "v".repeat(1000);
+        for i in 0..500 {
+            content.push_str(&format!("churn = {}{}\n", filler, i));
+        }
+        let parser = AAML::parse(&content).expect("Parsed");
+
+        assert_eq!(parser.find_obj_ref("alias"), Some("target"));
+        assert_eq!(parser.find_deep_ref("alias"), Some("shared"));
+        assert_eq!(parser.find_obj_ref("self"), Some("self"));
+        assert!(parser.find_obj_ref("gone").is_none());
+        assert_eq!(parser.find_obj("churn").unwrap().as_str(), format!("{}499", filler));
+        assert_eq!(parser.find_key_ref("shared"), Some("target"));
+    }
+}
@@ -2,6 +2,9 @@
 mod tests {
     use crate::aaml::AAML;
     use crate::builder::AAMBuilder;
+    use crate::error::AamlError;
+    use crate::types::primitive_type::PrimitiveType;
+    use crate::types::resolve_builtin;
     use std::fs;
 
     // ─────────────────────────────────────────────────────────────
@@ -111,6 +114,110 @@ mod tests {
         assert!(result.is_err());
     }
 
+    struct UpperCommand;
+
+    impl crate::commands::Command for UpperCommand {
+        fn name(&self) -> &str { "upper" }
+
+        fn execute(&self, aaml: &mut AAML, args: &str) -> Result<(), AamlError> {
+            let (key, value) = args.split_once('=').unwrap();
+            aaml.merge_content(&format!("{} = {}", key.trim(), value.trim().to_uppercase()))
+        }
+    }
+
+    #[test]
+    fn test_derive_carries_types_and_uses_child_commands() {
+        let base_file = "test_derive_types_base.aam";
+        let mut base = AAMBuilder::new();
+        base.add_raw("@type port = i32");
+        base.add_raw("@type level = string");
+        base.add_raw("@upper shout = hello");
+        base.to_file(base_file).unwrap();
+
+        let mut parser = AAML::new();
+        parser.register_command(UpperCommand);
+        let result = parser.merge_content(&format!("@type level = i32\n@derive {base_file}"));
+        let _ = fs::remove_file(base_file);
+        result.expect("Base should see the child's custom command");
+
+        assert_eq!(parser.find_obj("shout").unwrap().as_str(), "HELLO");
+        assert!(parser.validate_value("port", "80").is_ok());
+        assert!(parser.validate_value("port", "x").is_err());
+        // The child's own definition wins over the base's
+        assert!(parser.validate_value("level", "x").is_err());
+
+        let restored = AAML::from_compiled(&parser.to_compiled()).unwrap();
+        assert!(restored.validate_value("port", "x").is_err());
+    }
+
+    #[test]
+    fn test_derive_multiple_and_multi_level_bases() {
+        let (a, b, root) = ("test_derive_multi_a.aam", "test_derive_multi_b.aam", "test_derive_multi_root.aam");
+
+        let mut r = AAMBuilder::new();
+        r.add_line("shared", "from_root");
+        r.add_line("root_only", "r");
+        r.to_file(root).unwrap();
+
+        let mut ab = AAMBuilder::new();
+        ab.add_raw(&format!("@derive {root}"));
+        ab.add_line("shared", "from_a");
+        ab.to_file(a).unwrap();
+
+        let mut bb = AAMBuilder::new();
+        bb.add_line("shared", "from_b");
+        bb.add_line("b_only", "b");
+        bb.to_file(b).unwrap();
+
+        let a_first = AAML::parse(&format!("@derive {a}, \"{b}\""));
+        let b_first = AAML::parse(&format!("@derive {b}, {a}"));
+        for f in [a, b, root] {
+            let _ = fs::remove_file(f);
+        }
+
+        let a_first = a_first.expect("Should parse multiple bases");
+        assert_eq!(a_first.find_obj("shared").unwrap().as_str(), "from_a");
+        assert_eq!(a_first.find_obj("root_only").unwrap().as_str(), "r");
+        assert_eq!(a_first.find_obj("b_only").unwrap().as_str(), "b");
+
+        assert_eq!(b_first.unwrap().find_obj("shared").unwrap().as_str(), "from_b");
+        assert!(AAML::parse("@derive a.aam, ").is_err());
+    }
+
+    #[test]
+    fn test_derive_and_import_cycles_are_errors() {
+        let (a, b) = ("test_derive_cycle_a.aam", "test_derive_cycle_b.aam");
+        let mut ab = AAMBuilder::new();
+        ab.add_raw(&format!("@derive {b}"));
+        ab.to_file(a).unwrap();
+        let mut bb = AAMBuilder::new();
+        bb.add_raw(&format!("@derive {a}"));
+        bb.to_file(b).unwrap();
+
+        let (c, d) = ("test_import_cycle_c.aam", "test_import_cycle_d.aam");
+        let mut cb = AAMBuilder::new();
+        cb.add_raw(&format!("@import {d}"));
+        cb.to_file(c).unwrap();
+        let mut db = AAMBuilder::new();
+        db.add_raw(&format!("@import {c}"));
+        db.to_file(d).unwrap();
+
+        let derive_cycle = AAML::load(a);
+        let import_cycle = AAML::load(c);
+        for f in [a, b, c, d] {
+            let _ = fs::remove_file(f);
+        }
+
+        match derive_cycle {
+            Err(AamlError::DirectiveError(cmd, msg)) => {
+                assert_eq!(cmd, "derive");
+                assert!(msg.contains(a) && msg.contains(b), "{msg}");
+            }
+            other => panic!("Expected derive cycle error, got {other:?}"),
+        }
+        assert!(matches!(import_cycle, Err(AamlError::DirectiveError(cmd, _)) if cmd == "import"));
+    }
+
     // ─────────────────────────────────────────────────────────────
     //  @schema tests
     // ─────────────────────────────────────────────────────────────
@@ -172,6 +279,155 @@ mod tests {
         assert!(v3.fields.contains_key("z"));
     }
 
+    #[test]
+    fn test_schema_extends_and_mixins() {
+        let parser = AAML::parse(
+            "@schema Entity { id: i32, name: string }\n\
+             @schema Timestamps { created: string, updated: string }\n\
+             @type score = i32\n\
+             @schema Player extends Entity { ..Timestamps, score: score, id: score }",
+        )
+        .unwrap();
+
+        let player = parser.get_schema("Player").unwrap();
+        assert_eq!(player.extends.as_deref(), Some("Entity"));
+        assert_eq!(player.mixins, ["Timestamps"]);
+        assert_eq!(player.fields.len(), 2);
+
+        let resolved = parser.resolve_schema("Player").unwrap();
+        let names: Vec<_> = resolved.fields.iter().map(|f| f.name.as_str()).collect();
+        assert_eq!(names, ["id", "name", "created", "updated", "score"]);
+        assert_eq!(resolved.field("id").unwrap().type_name, "score");
+        assert_eq!(resolved.field("id").unwrap().origin, "Player");
+        assert_eq!(resolved.field("name").unwrap().origin, "Entity");
+        assert_eq!(resolved.field("created").unwrap().origin, "Timestamps");
+
+        let restored = AAML::from_compiled(&parser.to_compiled()).unwrap();
+        assert_eq!(restored.resolve_schema("Player").unwrap().fields, resolved.fields);
+    }
+
+    #[test]
+    fn test_schema_resolution_errors() {
+        let conflict = AAML::parse("@schema A { id: i32 }\n@schema B extends A { id: bool }").unwrap();
+        let cycle = AAML::parse("@schema A extends C { }\n@schema B { ..A }\n@schema C extends B { }").unwrap();
+        let unknown = AAML::parse("@schema A { ..Missing }").unwrap();
+
+        match conflict.resolve_schema("B") {
+            Err(AamlError::DirectiveError(_, msg)) => assert!(msg.contains("'id: bool' in 'B'"), "{msg}"),
+            other => panic!("Expected conflict, got {other:?}"),
+        }
+        match cycle.resolve_schema("A") {
+            Err(AamlError::DirectiveError(_, msg)) => assert!(msg.contains("A -> C -> B -> A"), "{msg}"),
+            other => panic!("Expected cycle, got {other:?}"),
+        }
+        assert!(matches!(unknown.resolve_schema("A"), Err(AamlError::DirectiveError(..))));
+        assert!(matches!(unknown.resolve_schema("Nope"), Err(AamlError::NotFound(_))));
+
+        assert!(AAML::parse("@schema A inherits B { }").is_err());
+        assert!(AAML::parse("@schema A { .. }").is_err());
+    }
+
+    #[test]
+    fn test_schema_inheritance_across_derive_and_namespaces() {
+        let base_file = "test_schema_inherit_base.aam";
+        let mut base = AAMBuilder::new();
+        base.add_raw("@schema Entity { id: i32, name: string }");
+        base.to_file(base_file).unwrap();
+
+        let derived = AAML::parse(&format!(
+            "@derive {base_file}\n@schema Plugin extends Entity {{ plugin_name: string }}"
+        ));
+        let namespaced = AAML::parse(&format!(
+            "@import {base_file} as core\n@schema Plugin extends core.Entity {{ enabled: bool }}"
+        ));
+        let _ = fs::remove_file(base_file);
+
+        let plugin = derived.unwrap().resolve_schema("Plugin").unwrap();
+        assert_eq!(plugin.fields.len(), 3);
+
+        let plugin = namespaced.unwrap().resolve_schema("Plugin").unwrap();
+        assert_eq!(plugin.field("id").unwrap().origin, "core.Entity");
+    }
+
+    #[test]
+    fn test_schema_optional_fields_defaults_and_constraints() {
+        let content = "@schema Server { host: string /^[a-z.]+$/, port: i32 (1..=65535) = 8080, \
+                       mode: enum(dev, prod) = dev, ratio?: f64 (0..1), tags?: string }\nserver.host = example.com\nserver.ratio = 0.5";
+        let mut parser = AAML::parse(content).unwrap();
+        let port = parser.resolve_schema("Server").unwrap().field("port").unwrap().rules.clone();
+        assert!(!port.optional);
+        assert_eq!(port.default.as_deref(), Some("8080"));
+
+        assert!(parser.find_obj("server.port").is_none());
+        parser.validate_schema("Server", "server").unwrap();
+        assert_eq!(parser.find_obj("server.port").unwrap().as_str(), "8080");
+        assert_eq!(parser.find_obj("server.mode").unwrap().as_str(), "dev");
+        assert!(parser.find_obj("server.tags").is_none());
+
+        let restored = AAML::from_compiled(&parser.to_compiled()).unwrap();
+        assert_eq!(restored.get_schema("Server").unwrap().rules, parser.get_schema("Server").unwrap().rules);
+    }
+
+    #[test]
+    fn test_schema_violations() {
+        let schema = "@schema S { host: string /^[a-z]+$/, port: i32 (1..=65535), mode: enum(dev, prod), ratio?: f64 (0..1) }";
+        let violation = |body: &str| {
+            let mut parser = AAML::parse(&format!("{schema}\n{body}")).unwrap();
+            match parser.validate_schema("S", "") {
+                Err(AamlError::SchemaViolation { key, details, .. }) => (key, details),
+                other => panic!("Expected violation for {body:?}, got {other:?}"),
+            }
+        };
+
+        assert_eq!(violation("port = 80\nmode = dev").0, "host");
+        assert_eq!(violation("host = Web1\nport = 80\nmode = dev").0, "host");
+        assert_eq!(violation("host = web\nport = 0\nmode = dev").0, "port");
+        assert!(violation("host = web\nport = http\nmode = dev").1.contains("expected i32"));
+        assert!(violation("host = web\nport = 80\nmode = test").1.contains("not one of dev, prod"));
+        assert_eq!(violation("host = web\nport = 80\nmode = dev\nratio = 1").0, "ratio");
+
+        assert!(AAML::parse("@schema S { port: i32 (1..=10) = 20 }").is_err());
+        assert!(AAML::parse("@schema S { port: i32 = abc }").is_err());
+        assert!(AAML::parse("@schema S { port: nope = 1 }").is_err());
+        assert!(AAML::parse("@schema S { timeout: physics::second (0..60) = 90 s }").is_err());
+        assert!(AAML::parse("@schema S { port: i32 (1..x) }").is_err());
+        assert!(AAML::parse("@schema S { name: string /[a-/ }").is_err());
+        assert!(AAML::parse("@schema S { mode: enum(dev, ) }").is_err());
+        assert!(matches!(AAML::new().validate_schema("Nope", ""), Err(AamlError::NotFound(_))));
+    }
+
+    #[test]
+    fn test_schema_defaults_are_checked_against_the_field_type() {
+        let mut parser = AAML::parse("@schema S { timeout: physics::second (0..60) = 30 s, retry: physics::second = 1 min }").unwrap();
+        parser.validate_schema("S", "job").unwrap();
+        assert_eq!(parser.find_obj("job.timeout").unwrap().as_str(), "30 s");
+        assert_eq!(parser.find_obj("job.retry").unwrap().as_str(), "1 min");
+
+        let mut parser = AAML::parse("@schema S { port: i32 = 80, host: string }").unwrap();
+        assert!(parser.validate_schema("S", "").is_err());
+        assert!(parser.find_obj("port").is_none());
+    }
+
+    #[test]
+    fn test_schema_defaults_keep_slashes_and_escapes() {
+        let parser = AAML::parse("@schema S { speed: string = 30 km/h, port: i32, path: string = \"C:\\\\tmp \\\"x\\\"\" }").unwrap();
+        let schema = parser.get_schema("S").unwrap();
+        assert_eq!(schema.fields.get("port").map(String::as_str), Some("i32"));
+        assert_eq!(schema.rules["speed"].default.as_deref(), Some("30 km/h"));
+        assert_eq!(schema.rules["path"].default.as_deref(), Some("C:\\tmp \"x\""));
+
+        let restored = AAML::from_compiled(&parser.to_compiled()).unwrap();
+        assert_eq!(restored.get_schema("S").unwrap().rules, schema.rules);
+    }
+
+    #[test]
+    fn test_schema_overridden_field_replaces_rules() {
+        let content = "@schema A { port: i32 (1..=100) }\n@schema B extends A { port?: i32 }\nport = 500";
+        let mut parser = AAML::parse(content).unwrap();
+        assert!(parser.validate_schema("A", "").is_err());
+        assert!(parser.validate_schema("B", "").is_ok());
+    }
+
     // ─────────────────────────────────────────────────────────────
     //  @type tests
     // ─────────────────────────────────────────────────────────────
@@ -222,6 +478,51 @@ mod tests {
         assert!(parser.validate_value("created_at", "bad-date").is_err());
     }
 
+    #[test]
+    fn test_type_integer_and_float_family() {
+        let valid = |ty: &str, value: &str| resolve_builtin(ty).unwrap().validate(value).is_ok();
+
+        assert!(valid("i8", "-128") && !valid("i8", "128"));
+        assert!(valid("u8", "255") && !valid("u8", "-1"));
+        assert!(valid("u16", "65535") && !valid("u16", "65536"));
+        assert!(valid("i64", "-9223372036854775808") && !valid("i32", "2147483648"));
+        assert!(valid("u64", "18446744073709551615") && !valid("u32", "4294967296"));
+        assert!(valid("i128", "-170141183460469231731687303715884105728"));
+        assert!(valid("u128", "340282366920938463463374607431768211455") && !valid("u128", "1.0"));
+        assert!(valid("f32", "3.5") && valid("f32", "inf") && !valid("f32", "1e40"));
+        assert!(valid("char", "é") && !valid("char", "ab") && !valid("char", ""));
+
+        let parser = AAML::parse("@type port = u16 where 1..").unwrap();
+        assert!(parser.validate_value("port", "0").is_err());
+        assert!(parser.validate_value("port", "65535").is_ok());
+    }
+
+    #[test]
+    fn test_type_string_formats() {
+        let valid = |ty: &str, value: &str| resolve_builtin(ty).unwrap().validate(value).is_ok();
+
+        assert!(valid("uuid", "550e8400-e29b-41d4-a716-446655440000"));
+        assert!(!valid("uuid", "550e8400-e29b-41d4-a716-44665544000g"));
+        assert!(valid("email", "first.last+tag@example.co.uk"));
+        assert!(!valid("email", "no-at.example.com") && !valid("email", "a@localhost") && !valid("email", "a..b@x.io"));
+        assert!(valid("url", "https://example.com/path?q=1") && valid("url", "postgres://user@db:5432"));
+        assert!(!valid("url", "example.com") && !valid("url", "http:///x") && !valid("url", "http://a b"));
+        assert!(valid("ipv4", "192.168.0.1") && !valid("ipv4", "256.0.0.1"));
+        assert!(valid("ipv6", "::1") && valid("ipv6", "fe80::1ff:fe23:4567:890a") && !valid("ipv6", "1.2.3.4"));
+        assert!(valid("socket_addr", "127.0.0.1:8080") && valid("socket_addr", "[::1]:443"));
+        assert!(!valid("socket_addr", "localhost:80") && !valid("socket_addr", "127.0.0.1"));
+        assert!(valid("path", "./config/app.aam") && !valid("path", ""));
+        assert!(valid("semver", "1.0.0") && valid("semver", "2.1.3-rc.1+build.5"));
+        assert!(!valid("semver", "1.0") && !valid("semver", "01.0.0") && !valid("semver", "1.0.0-01"));
+        assert!(valid("base64", "aGVsbG8=") && valid("base64", "aGk/+A==") && !valid("base64", "aGVsbG8") && !valid("base64", "a==="));
+        assert!(valid("hex", "0xDEADbeef") && valid("hex", "ff00") && !valid("hex", "0x") && !valid("hex", "xyz"));
+
+        match resolve_builtin("email").unwrap().validate("nope") {
+            Err(AamlError::InvalidValue(msg)) => assert_eq!(msg, "Expected email, got 'nope'"),
+            other => panic!("Expected InvalidValue, got {other:?}"),
+        }
+    }
+
     #[test]
     fn test_type_missing_name_error() {
         let result = AAML::parse("@type = i32");
@@ -234,11 +535,185 @@ mod tests {
         assert!(result.is_err());
     }
 
+    #[test]
+    fn test_type_refined_range_enum_and_pattern() {
+        let content = "@type port = i32 where 1..=65535\n\
+                       @type ratio = f64 where 0..1\n\
+                       @type level = enum { debug, info, warn }\n\
+                       @type slug = string matching \"[a-z-]+\"";
+        let parser = AAML::parse(content).expect("Should parse refined types");
+
+        assert!(parser.validate_value("port", "65535").is_ok());
+        assert!(parser.validate_value("port", "0").is_err());
+        assert!(parser.validate_value("port", "http").is_err());
+        assert!(parser.validate_value("ratio", "0.25").is_ok());
+        assert!(parser.validate_value("ratio", "1").is_err());
+        assert!(parser.validate_value("level", "info").is_ok());
+        assert!(parser.validate_value("slug", "my-post").is_ok());
+        assert!(parser.validate_value("slug", "My post").is_err());
+
+        match parser.validate_value("level", "trace") {
+            Err(AamlError::InvalidType { type_name, details }) => {
+                assert_eq!(type_name, "level");
+                assert!(details.contains("not one of debug, info, warn"), "{details}");
+            }
+            other => panic!("Expected InvalidType, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn test_type_alias_of_user_type() {
+        let content = "@type port = i32 where 1..=65535\n\
+                       @type admin_port = port where 1024..\n\
+                       @type alias_port = admin_port";
+        let parser = AAML::parse(content).expect("Should parse aliases");
+        assert!(parser.validate_value("alias_port", "8080").is_ok());
+        assert!(parser.validate_value("alias_port", "80").is_err());
+        assert!(parser.validate_value("alias_port", "70000").is_err());
+        assert_eq!(parser.get_type("alias_port").unwrap().base_type().to_string(), "i32");
+
+        // `alias_port` sorts before its target and is replayed after it.
+        let restored = AAML::from_compiled(&parser.to_compiled()).unwrap();
+        assert!(restored.validate_value("alias_port", "80").is_err());
+        assert!(restored.validate_value("alias_port", "8080").is_ok());
+    }
+
+    #[test]
+    fn test_type_alias_follows_redefined_target() {
+        let content = "@type small = i32 where 1..=10\n@type level = small\n@type small = i32 where 1..=3";
+        let parser = AAML::parse(content).unwrap();
+        assert!(parser.validate_value("level", "5").is_err());
+        assert!(parser.validate_value("level", "2").is_ok());
+
+        let restored = AAML::from_compiled(&parser.to_compiled()).unwrap();
+        assert!(restored.validate_value("level", "5").is_err());
+
+        let mut merged = AAML::parse("@type small = i32\n@type level = small where 1..").unwrap();
+        merged += AAML::parse("@type small = u8").unwrap();
+        assert!(merged.validate_value("level", "300").is_err());
+
+        match AAML::parse("@type n = i32\n@type r = n where 1..=5\n@type n = string") {
+            Err(AamlError::InvalidType { type_name, .. }) => assert_eq!(type_name, "r"),
+            other => panic!("Expected InvalidType, got {other:?}"),
+        }
+        assert!(AAML::parse("@type a = i32\n@type b = a\n@type a = b").is_err());
+        assert!(AAML::parse("@type a = i32\n@type a = a where 1..=5").is_err());
+    }
+
+    #[test]
+    fn test_conflicting_type_keeps_previous_binding() {
+        let mut parser = AAML::parse("@type n = i32\n@type r = n where 1..=5\nkey = value").unwrap();
+        let err = parser.register_type("n".to_string(), PrimitiveType::String);
+        assert!(matches!(err, Err(AamlError::InvalidType { type_name, .. }) if type_name == "r"));
+        assert!(parser.validate_value("n", "abc").is_err());
+        assert!(parser.validate_value("r", "3").is_ok());
+        assert!(parser.validate_value("r", "9").is_err());
+
+        let err = parser.merge(AAML::parse("@type n = string\nkey = other").unwrap());
+        assert!(matches!(err, Err(AamlError::InvalidType { type_name, .. }) if type_name == "r"));
+        assert!(parser.validate_value("r", "3").is_ok());
+        assert_eq!(parser.find_obj_ref("key"), Some("value"));
+
+        assert!(parser.register_type("n".to_string(), PrimitiveType::U8).is_ok());
+        assert!(parser.validate_value("r", "3").is_ok());
+        assert!(parser.validate_value("r", "-1").is_err());
+    }
+
+    #[test]
+    fn test_type_refinement_errors() {
+        let invalid = |definition: &str| match AAML::parse(&format!("@type t = {definition}")) {
+            Err(AamlError::InvalidType { details, .. }) => details,
+            other => panic!("Expected InvalidType for {definition:?}, got {other:?}"),
+        };
+        assert!(invalid("nope").contains("unknown type 'nope'"));
+        assert!(invalid("math::nope").contains("unknown type"));
+        assert!(invalid("string where 1..5").contains("numeric base type"));
+        assert!(invalid("i32 where 1..x").contains("bad range bound"));
+        assert!(invalid("string matching [a-z]+").contains("quoted pattern"));
+        assert!(invalid("string matching \"[a-\"").contains("regex"));
+        assert!(invalid("enum { }").contains("at least one value"));
+        assert!(invalid("i32 between 1 5").contains("expected 'where' or 'matching'"));
+    }
+
     #[test]
     fn test_validate_value_unknown_type() {
         let parser = AAML::parse("").unwrap();
         let result = parser.validate_value("unknown_type", "value");
         assert!(result.is_err());
     }
-}
 
+    // ─────────────────────────────────────────────────────────────
+    //  @unset / @require tests
+    // ─────────────────────────────────────────────────────────────
+
+    fn write_service_base(file: &str) {
+        let mut base = AAMBuilder::new();
+        base.add_raw("@require api_key, region");
+        base.add_line("host", "api.example.com");
+        base.add_line("debug_port", "9229");
+        base.add_line("legacy_mode", "on");
+        base.to_file(file).unwrap();
+    }
+
+    #[test]
+    fn test_unset_removes_inherited_keys() {
+        let base_file = "test_unset_base.aam";
+        write_service_base(base_file);
+
+        let content = format!(
+            "@derive {base_file}\n@unset debug_port, legacy_mode, never_defined\napi_key = k\nregion = eu\n"
+        );
+        let parser = AAML::parse(&content);
+        let _ = fs::remove_file(base_file);
+        let mut parser = parser.expect("Should parse @unset");
+
+        assert_eq!(parser.find_obj("host").unwrap().as_str(), "api.example.com");
+        assert!(parser.find_obj("debug_port").is_none());
+        assert!(parser.find_obj("legacy_mode").is_none());
+
+        parser.enable_reverse_index();
+        parser.merge_content("@unset host").unwrap();
+        assert!(parser.find_key("api.example.com").is_none());
+    }
+
+    #[test]
+    fn test_require_is_checked_after_all_merges() {
+        let base_file = "test_require_base.aam";
+        write_service_base(base_file);
+
+        let satisfied = AAML::parse(&format!("@derive {base_file}\napi_key = k\nregion = eu"));
+        let missing = AAML::parse(&format!("@derive {base_file}\nregion = eu"));
+        let _ = fs::remove_file(base_file);
+
+        assert!(satisfied.is_ok());
+        match missing {
+            Err(AamlError::MissingKeys(keys)) => assert_eq!(keys, ["api_key"]),
+            other => panic!("Expected MissingKeys, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn test_require_after_unset_and_in_namespaced_import() {
+        let module = "test_require_module.aam";
+        let mut b = AAMBuilder::new();
+        b.add_raw("@require password");
+        b.add_line("user", "app");
+        b.to_file(module).unwrap();
+
+        let provided = AAML::parse(&format!("@import {module} as db\ndb.password = secret"));
+        let unprefixed = AAML::parse(&format!("@import {module} as db\npassword = secret"));
+        let not_selected = AAML::parse(&format!("@import {module} only {{ user }}"));
+        let _ = fs::remove_file(module);
+
+        assert!(provided.is_ok());
+        assert!(matches!(unprefixed, Err(AamlError::MissingKeys(keys)) if keys == ["db.password"]));
+        assert!(not_selected.is_ok());
+
+        let result = AAML::parse("@require a, b\na = 1\nb = 2\n@unset b");
+        assert!(matches!(result, Err(AamlError::MissingKeys(keys)) if keys == ["b"]));
+
+        assert!(AAML::parse("@if 0\n@require nope\n@endif").is_ok());
+        assert!(matches!(AAML::parse("@require"), Err(AamlError::DirectiveError(..))));
+        assert!(matches!(AAML::parse("@unset ,"), Err(AamlError::DirectiveError(..))));
+    }
+}
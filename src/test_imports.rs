@@ -2,6 +2,8 @@
 mod tests {
     use crate::aaml::AAML;
     use crate::builder::AAMBuilder;
+    use crate::commands::import::wildcard_match;
+    use crate::error::AamlError;
     use std::fs;
 
 
@@ -82,4 +84,202 @@ mod tests {
         let parser = parser.expect("Should parse quoted import path");
         assert_eq!(parser.find_obj("q_key").unwrap().as_str(), "q_val");
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_consecutive_imports_apply_in_order() {
+        let files = ["order_import_1.aam", "order_import_2.aam", "order_import_3.aam"];
+        for (i, file) in files.iter().enumerate() {
+            let mut b = AAMBuilder::new();
+            b.add_line("shared", &format!("from_{}", i + 1));
+            b.add_line(&format!("only_{}", i + 1), "yes");
+            b.to_file(file).unwrap();
+        }
+
+        let content = format!(
+            "shared = from_main\n@import {}\n\n@import \"{}\"\nbetween = x\n@import {}\n",
+            files[0], files[1], files[2]
+        );
+        let parser = AAML::parse(&content);
+        for file in files {
+            let _ = fs::remove_file(file);
+        }
+        let parser = parser.expect("Should parse consecutive imports");
+
+        assert_eq!(parser.find_obj("shared").unwrap().as_str(), "from_3");
+        assert_eq!(parser.find_obj("only_1").unwrap().as_str(), "yes");
+        assert_eq!(parser.find_obj("only_2").unwrap().as_str(), "yes");
+        assert_eq!(parser.find_obj("only_3").unwrap().as_str(), "yes");
+    }
+
+    #[test]
+    fn test_missing_import_in_run_fails() {
+        let file = "run_import_present.aam";
+        let mut b = AAMBuilder::new();
+        b.add_line("present", "yes");
+        b.to_file(file).unwrap();
+
+        let content = format!("@import {file}\n@import run_import_missing_xyz.aam\n");
+        let result = AAML::parse(&content);
+        let _ = fs::remove_file(file);
+
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_wildcard_match() {
+        assert!(wildcard_match("*.aam", "base.aam"));
+        assert!(!wildcard_match("*.aam", "base.aam.bak"));
+        assert!(wildcard_match("1?-*.aam", "10-db.aam"));
+        assert!(wildcard_match("[0-9]*", "5x"));
+        assert!(!wildcard_match("[!0-9]*", "5x"));
+        assert!(wildcard_match("a*b*c", "aXbYbZc"));
+        assert!(wildcard_match("[]", "[]"));
+    }
+
+    #[test]
+    fn test_glob_import_merges_in_sorted_order() {
+        let dir = "glob_import_conf.d";
+        let _ = fs::remove_dir_all(dir);
+        fs::create_dir_all(dir).unwrap();
+        for (name, value) in [("20-b.aam", "b"), ("10-a.aam", "a"), ("30-c.aam", "c"), ("notes.txt", "x"), (".hidden.aam", "h")] {
+            let mut b = AAMBuilder::new();
+            b.add_line("winner", value);
+            b.add_line(&format!("from_{}", name.replace(['.', '-'], "_")), "yes");
+            b.to_file(format!("{dir}/{name}")).unwrap();
+        }
+
+        let result = AAML::parse(&format!("winner = main\n@import \"{dir}/*.aam\""));
+        let _ = fs::remove_dir_all(dir);
+        let parser = result.expect("Should import every matching file");
+
+        assert_eq!(parser.find_obj("winner").unwrap().as_str(), "c");
+        assert!(parser.find_obj("from_10_a_aam").is_some());
+        assert!(parser.find_obj("from_notes_txt").is_none());
+        assert!(parser.find_obj("from__hidden_aam").is_none());
+    }
+
+    #[test]
+    fn test_glob_import_without_matches_fails() {
+        let err = AAML::parse("@import glob_import_missing_dir/*.aam").unwrap_err();
+        match err {
+            AamlError::DirectiveError(cmd, msg) => {
+                assert_eq!(cmd, "import");
+                assert!(msg.contains("glob_import_missing_dir/*.aam"));
+            }
+            other => panic!("Expected DirectiveError, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn test_optional_import() {
+        let file = "optional_import_local.aam";
+        let mut b = AAMBuilder::new();
+        b.add_line("mode", "local");
+        b.to_file(file).unwrap();
+
+        let content = format!(
+            "mode = default\n@import? optional_import_absent.aam\n@import? \"optional_import_none_*.aam\"\n@import? {file}\n"
+        );
+        let result = AAML::parse(&content);
+        let _ = fs::remove_file(file);
+
+        assert_eq!(result.unwrap().find_obj("mode").unwrap().as_str(), "local");
+        assert!(AAML::parse("@import?").is_err());
+    }
+
+    fn write_db_module(file: &str) {
+        let mut b = AAMBuilder::new();
+        b.add_raw("@type port = i32 where 1..=65535");
+        b.add_raw("@type admin_port = port");
+        b.add_raw("@schema Conn { host: string, port: port }");
+        b.add_line("host", "localhost");
+        b.add_line("port", "5432");
+        b.add_line("timeout", "5");
+        b.add_line("url", "host");
+        b.to_file(file).unwrap();
+    }
+
+    #[test]
+    fn test_namespaced_import() {
+        let file = "ns_import_db.aam";
+        write_db_module(file);
+
+        let result = AAML::parse(&format!("timeout = 30\n@import \"{file}\" as db"));
+        let _ = fs::remove_file(file);
+        let parser = result.expect("Should parse namespaced import");
+
+        assert_eq!(parser.find_obj("timeout").unwrap().as_str(), "30");
+        assert_eq!(parser.find_obj("db.timeout").unwrap().as_str(), "5");
+        assert_eq!(parser.find_obj("db.url").unwrap().as_str(), "host");
+        assert_eq!(parser.find_deep("db.url").unwrap().as_str(), "localhost");
+        assert!(!parser.contains_key("host"));
+
+        assert!(parser.get_type("port").is_none());
+        assert!(parser.validate_value("db.port", "5432").is_ok());
+        assert!(parser.validate_value("db.port", "x").is_err());
+        assert!(parser.validate_value("db.admin_port", "0").is_err());
+        let restored = AAML::from_compiled(&parser.to_compiled()).unwrap();
+        assert!(restored.validate_value("db.admin_port", "0").is_err());
+        assert_eq!(restored.find_deep("db.url").unwrap().as_str(), "localhost");
+        assert!(parser.get_schema("Conn").is_none());
+        assert_eq!(parser.get_schema("db.Conn").unwrap().fields["port"], "db.port");
+        assert_eq!(parser.get_schema("db.Conn").unwrap().fields["host"], "string");
+    }
+
+    #[test]
+    fn test_namespaced_import_keeps_values_naming_keys() {
+        let inner = "ns_values_inner.aam";
+        let outer = "ns_values_outer.aam";
+        fs::write(inner, "engine = postgres\nlabel = engine").unwrap();
+        fs::write(outer, format!("engine = mysql\ndb = engine\n@import {inner} as sub\nref = sub.label")).unwrap();
+
+        let result = AAML::parse(&format!("engine = sqlite\n@import {outer} as app"));
+        let _ = fs::remove_file(inner);
+        let _ = fs::remove_file(outer);
+        let parser = result.expect("Should parse nested namespaced imports");
+
+        assert_eq!(parser.find_obj("app.db").unwrap().as_str(), "engine");
+        assert_eq!(parser.find_deep("app.db").unwrap().as_str(), "mysql");
+        assert_eq!(parser.find_obj("app.sub.label").unwrap().as_str(), "engine");
+        assert_eq!(parser.find_deep("app.sub.label").unwrap().as_str(), "postgres");
+        assert_eq!(parser.find_deep("app.ref").unwrap().as_str(), "postgres");
+        assert_eq!(parser.find_deep("engine").unwrap().as_str(), "sqlite");
+    }
+
+    #[test]
+    fn test_selective_import() {
+        let file = "only_import_db.aam";
+        write_db_module(file);
+
+        let only = AAML::parse(&format!("@import {file} only {{ host, port }}"));
+        let both = AAML::parse(&format!("@import {file} as db only {{ url }}"));
+        let missing = AAML::parse(&format!("@import {file} only {{ host, nope }}"));
+        let _ = fs::remove_file(file);
+
+        let only = only.expect("Should parse selective import");
+        assert_eq!(only.find_obj("host").unwrap().as_str(), "localhost");
+        assert_eq!(only.find_obj("port").unwrap().as_str(), "5432");
+        assert!(only.find_obj("timeout").is_none());
+
+        let both = both.expect("Should parse namespaced selective import");
+        assert_eq!(both.find_obj("db.url").unwrap().as_str(), "host");
+        assert!(both.find_obj("db.host").is_none());
+
+        match missing.unwrap_err() {
+            AamlError::DirectiveError(cmd, msg) => {
+                assert_eq!(cmd, "import");
+                assert!(msg.contains("nope") && !msg.contains("host"));
+            }
+            other => panic!("Expected DirectiveError, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn test_import_clause_errors() {
+        assert!(matches!(AAML::parse("@import a.aam as"), Err(AamlError::DirectiveError(..))));
+        assert!(matches!(AAML::parse("@import a.aam only host"), Err(AamlError::DirectiveError(..))));
+        assert!(matches!(AAML::parse("@import a.aam only { }"), Err(AamlError::DirectiveError(..))));
+        assert!(matches!(AAML::parse("@import \"a.aam\" extra"), Err(AamlError::DirectiveError(..))));
+        assert!(AAML::parse("@import? ns_import_absent.aam as db").is_ok());
+    }
+}
@@ -0,0 +1,49 @@
+#[cfg(all(test, feature = "parallel"))]
+mod tests {
+    use crate::aaml::AAML;
+    use crate::lexer;
+    use crate::parallel::{lex_in_chunks, split_at_lines};
+
+    #[test]
+    fn test_split_at_lines_keeps_whole_lines() {
+        let content = "a = 1\nb = 2\nc = 3\nd = 4\ne = 5";
+        let chunks = split_at_lines(content, 3);
+
+        assert!(chunks.len() <= 3);
+        assert_eq!(chunks.concat(), content);
+        for chunk in &chunks[..chunks.len() - 1] {
+            assert!(chunk.ends_with('\n'));
+        }
+    }
+
+    #[test]
+    fn test_split_at_lines_single_long_line() {
+        let content = "key = a_single_line_without_newlines";
+        assert_eq!(split_at_lines(content, 4), vec![content]);
+    }
+
+    #[test]
+    fn test_chunked_lexing_matches_sequential() {
+        let content: String = (0..500)
+            .map(|i| match i % 5 {
+                0 => format!("# comment {i}\n"),
+                1 => format!("@type t{i} = i32\n"),
+                2 => "\n".to_string(),
+                _ => format!("key_{i} = \"value # {i}\"\n"),
+            })
+            .collect();
+
+        let sequential: Vec<_> = content.lines().map(lexer::lex_line).collect();
+        for workers in [2, 3, 8] {
+            let chunked: Vec<_> = lex_in_chunks(&content, workers).into_iter().flatten().collect();
+            assert_eq!(chunked, sequential);
+        }
+    }
+
+    #[test]
+    fn test_imports_in_inactive_blocks_are_not_read() {
+        let content = "@if false\n@import \"missing_a.aam\"\n@import \"missing_b.aam\"\n@endif\nkey = value";
+        let parser = AAML::parse(content).expect("Parsed");
+        assert_eq!(parser.find_obj_ref("key"), Some("value"));
+    }
+}
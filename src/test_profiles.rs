@@ -0,0 +1,171 @@
+#[cfg(test)]
+mod tests {
+    use crate::aaml::AAML;
+    use crate::builder::AAMBuilder;
+    use crate::error::AamlError;
+    use crate::options::ParseOptions;
+    use std::fs;
+    use std::io::Cursor;
+
+    const PROFILED: &str = r#"
+@profile staging {
+    host = staging.example.com
+    replicas = 2
+}
+host = localhost
+port = 8080
+replicas = 1
+@profile local {
+    host = 127.0.0.1
+}
+port@local = 3000
+replicas@local = 0
+"#;
+
+    fn parse(profiles: &[&str]) -> AAML {
+        let options = profiles.iter().fold(ParseOptions::new(), |o, p| o.profile(*p));
+        AAML::parse_with_options(PROFILED, options).unwrap()
+    }
+
+    #[test]
+    fn test_without_profiles_uses_defaults() {
+        let parser = AAML::parse(PROFILED).unwrap();
+        assert_eq!(parser.find_obj("host").unwrap().as_str(), "localhost");
+        assert_eq!(parser.find_obj("port").unwrap().as_str(), "8080");
+        assert!(parser.find_obj("port@local").is_none());
+        assert_eq!(parser.list_profiles(), ["staging", "local"]);
+    }
+
+    #[test]
+    fn test_profile_overrides_plain_assignments() {
+        let parser = parse(&["staging"]);
+        assert_eq!(parser.find_obj("host").unwrap().as_str(), "staging.example.com");
+        assert_eq!(parser.find_obj("replicas").unwrap().as_str(), "2");
+        assert_eq!(parser.find_obj("port").unwrap().as_str(), "8080");
+    }
+
+    #[test]
+    fn test_profiles_apply_in_priority_order() {
+        let parser = parse(&["local", "staging"]);
+        assert_eq!(parser.find_obj("host").unwrap().as_str(), "127.0.0.1");
+        assert_eq!(parser.find_obj("port").unwrap().as_str(), "3000");
+        assert_eq!(parser.find_obj("replicas").unwrap().as_str(), "0");
+
+        let parser = parse(&["staging", "local"]);
+        assert_eq!(parser.find_obj("host").unwrap().as_str(), "staging.example.com");
+        assert_eq!(parser.find_obj("replicas").unwrap().as_str(), "2");
+        assert_eq!(parser.find_obj("port").unwrap().as_str(), "3000");
+    }
+
+    #[test]
+    fn test_load_with_profile_and_imports() {
+        let sub = "profile_import_sub.aam";
+        let mut b = AAMBuilder::new();
+        b.add_line("pool", "5");
+        b.add_line("pool@prod", "50");
+        b.to_file(sub).unwrap();
+
+        let extra = "profile_import_extra.aam";
+        let mut b = AAMBuilder::new();
+        b.add_line("cache", "on");
+        b.to_file(extra).unwrap();
+
+        let main = "profile_import_main.aam";
+        let mut b = AAMBuilder::new();
+        b.add_raw(&format!("@import {sub}"));
+        b.add_raw("@profile eu {");
+        b.add_raw(&format!("@import {extra}"));
+        b.add_raw("}");
+        b.add_line("cache", "off");
+        b.to_file(main).unwrap();
+
+        let prod = AAML::load_with_profile(main, "prod");
+        let both = AAML::load_with_profile(main, "eu, prod");
+        let _ = fs::remove_file(sub);
+        let _ = fs::remove_file(extra);
+        let _ = fs::remove_file(main);
+
+        let prod = prod.unwrap();
+        assert_eq!(prod.find_obj("pool").unwrap().as_str(), "50");
+        assert_eq!(prod.find_obj("cache").unwrap().as_str(), "off");
+        assert_eq!(prod.list_profiles(), ["prod", "eu"]);
+
+        let both = both.unwrap();
+        assert_eq!(both.options().profiles(), ["eu", "prod"]);
+        assert_eq!(both.find_obj("cache").unwrap().as_str(), "on");
+    }
+
+    #[test]
+    fn test_profile_block_errors() {
+        let cases = [
+            ("}", 1, "Missing assignment operator"),
+            ("k = v\n@if 1\n}\n@endif", 3, "inside @if block"),
+            ("@profile a\nk = v", 1, "Expected '@profile <name> {'"),
+            ("@profile a {\nk = v", 1, "Unterminated @profile"),
+            ("@profile a {\n@if 1\n}\n@endif", 3, "inside @if block"),
+            ("@if 1\n@profile a {\n@endif\n}", 3, "inside @profile block"),
+        ];
+
+        for (content, expected_line, expected) in cases {
+            match AAML::parse(content) {
+                Err(AamlError::ParseError { line, details, .. }) => {
+                    assert_eq!(line, expected_line, "{content}");
+                    assert!(details.contains(expected), "{content}: {details}");
+                }
+                other => panic!("Expected ParseError for {content:?}, got {other:?}"),
+            }
+        }
+    }
+
+    #[test]
+    fn test_at_sign_in_keys_without_declared_profile() {
+        let parser = AAML::parse("admin@corp = alice\nport@local = 3000").unwrap();
+        assert_eq!(parser.find_obj("admin@corp").unwrap().as_str(), "alice");
+        assert_eq!(parser.find_obj("port@local").unwrap().as_str(), "3000");
+        assert!(parser.list_profiles().is_empty());
+
+        let options = ParseOptions::new().profile("local");
+        let parser = AAML::parse_with_options("admin@corp = alice\nport = 80\nport@local = 3000", options).unwrap();
+        assert_eq!(parser.find_obj("admin@corp").unwrap().as_str(), "alice");
+        assert_eq!(parser.find_obj("port").unwrap().as_str(), "3000");
+        assert_eq!(parser.list_profiles(), ["local"]);
+    }
+
+    #[test]
+    fn test_later_files_override_profile_values() {
+        let sub = "profile_scope_sub.aam";
+        let late = "profile_scope_late.aam";
+        fs::write(sub, "@profile prod {\npool = 50\n}\npool = 5").unwrap();
+        fs::write(late, "pool = 7").unwrap();
+
+        let options = ParseOptions::new().profile("prod");
+        let imported = AAML::parse_with_options(&format!("@import {sub}"), options.clone());
+        let overridden = AAML::parse_with_options(&format!("@import {sub}\npool = 6"), options.clone());
+        let late_import = AAML::parse_with_options(&format!("@import {sub}\n@import {late}"), options);
+        let _ = fs::remove_file(sub);
+        let _ = fs::remove_file(late);
+
+        assert_eq!(imported.unwrap().find_obj("pool").unwrap().as_str(), "50");
+        assert_eq!(overridden.unwrap().find_obj("pool").unwrap().as_str(), "6");
+        assert_eq!(late_import.unwrap().find_obj("pool").unwrap().as_str(), "7");
+    }
+
+    #[test]
+    fn test_profile_ranks_reset_between_merges() {
+        let options = ParseOptions::new().profile("a").profile("b");
+        let mut parser = AAML::parse_with_options("@profile a {\nk = 1\n}", options).unwrap();
+        parser.merge_content("@profile b {\nk = 2\n}").unwrap();
+        assert_eq!(parser.find_obj("k").unwrap().as_str(), "2");
+    }
+
+    #[test]
+    fn test_reader_and_compiled_keep_profiles() {
+        let mut parser = AAML::new();
+        parser.merge_reader(Cursor::new(PROFILED)).unwrap();
+        assert_eq!(parser.list_profiles(), ["staging", "local"]);
+
+        let restored = AAML::from_compiled(&parser.to_compiled()).unwrap();
+        assert_eq!(restored.list_profiles(), ["staging", "local"]);
+        assert_eq!(restored.find_obj("host").unwrap().as_str(), "localhost");
+    }
+}
@@ -0,0 +1,82 @@
+#[cfg(test)]
+mod tests {
+    use crate::aaml::AAML;
+    use crate::error::AamlError;
+    use crate::reader::{AamlReader, Event, Span};
+    use std::io::Cursor;
+
+    const DOC: &str = "# header\nhost = \"localhost\" # trailing\n\n@type port = i32\nport = 8080\n";
+
+    fn events(content: &str) -> Vec<Result<Event, AamlError>> {
+        AamlReader::new(Cursor::new(content)).collect()
+    }
+
+    #[test]
+    fn test_reader_yields_events_in_order() {
+        let events: Vec<Event> = events(DOC).into_iter().map(|e| e.expect("Valid event")).collect();
+
+        assert_eq!(events, vec![
+            Event::Comment { text: "header".into(), span: Span { line: 1, start: 0, end: 8 } },
+            Event::Assignment { key: "host".into(), value: "localhost".into(), span: Span { line: 2, start: 9, end: 38 } },
+            Event::Comment { text: "trailing".into(), span: Span { line: 2, start: 9, end: 38 } },
+            Event::Directive { name: "type".into(), args: "port = i32".into(), span: Span { line: 4, start: 40, end: 56 } },
+            Event::Assignment { key: "port".into(), value: "8080".into(), span: Span { line: 5, start: 57, end: 68 } },
+        ]);
+    }
+
+    #[test]
+    fn test_reader_span_matches_source() {
+        for event in events(DOC) {
+            let span = event.unwrap().span();
+            let line = DOC.lines().nth(span.line - 1).unwrap();
+            assert_eq!(&DOC[span.start..span.end], line);
+        }
+    }
+
+    #[test]
+    fn test_reader_crlf_and_quoted_hash() {
+        let events = events("a = \"x # y\"\r\nb = 2\r\n");
+        assert_eq!(events.len(), 2);
+        match &events[0] {
+            Ok(Event::Assignment { value, span, .. }) => {
+                assert_eq!(value, "x # y");
+                assert_eq!((span.start, span.end), (0, 11));
+            }
+            other => panic!("Expected assignment, got {:?}", other),
+        }
+        assert_eq!(events[1].as_ref().unwrap().span().start, 13);
+    }
+
+    #[test]
+    fn test_reader_reports_errors_and_continues() {
+        let events = events("good = 1\nbroken\n@\nafter = 2");
+
+        assert!(events[0].is_ok());
+        assert!(matches!(events[1], Err(AamlError::ParseError { line: 2, .. })));
+        assert!(matches!(events[2], Err(AamlError::ParseError { line: 3, .. })));
+        assert!(matches!(&events[3], Ok(Event::Assignment { key, .. }) if key == "after"));
+    }
+
+    #[test]
+    fn test_reader_filters_without_map() {
+        let content: String = (0..1000).map(|i| format!("key_{i} = {i}\n")).collect();
+        let hits: Vec<String> = AamlReader::new(Cursor::new(content))
+            .filter_map(Result::ok)
+            .filter_map(|e| match e {
+                Event::Assignment { key, value, .. } if key.ends_with("99") => Some(value),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(hits, vec!["99", "199", "299", "399", "499", "599", "699", "799", "899", "999"]);
+    }
+
+    #[test]
+    fn test_from_reader_matches_parse() {
+        let streamed = AAML::from_reader(Cursor::new(DOC)).expect("Should stream");
+        assert_eq!(streamed.find_obj("host").unwrap().as_str(), "localhost");
+        assert!(streamed.validate_value("port", "8080").is_ok());
+
+        let err = AAML::from_reader(Cursor::new("@unknown thing")).unwrap_err();
+        assert!(err.to_string().contains("Unknown directive: @unknown"));
+    }
+}
@@ -0,0 +1,50 @@
+#[cfg(test)]
+mod tests {
+    use crate::aaml::AAML;
+    use crate::snapshot::AamlSnapshot;
+    use std::thread;
+
+    fn assert_shareable<T: Send + Sync + Clone + 'static>() {}
+
+    #[test]
+    fn test_snapshot_is_send_sync_clone() {
+        assert_shareable::<AamlSnapshot>();
+    }
+
+    #[test]
+    fn test_snapshot_read_apis() {
+        let snapshot = AAML::parse("@type port = i32\nhost = localhost\nport = 8080\nalias = host")
+            .expect("Parsed")
+            .freeze();
+
+        assert_eq!(snapshot.find_obj("host").unwrap().as_str(), "localhost");
+        assert_eq!(snapshot.find_deep_ref("alias"), Some("localhost"));
+        assert_eq!(snapshot.find_key_ref("8080"), Some("port"));
+        assert!(snapshot.validate_value("port", "8080").is_ok());
+    }
+
+    #[test]
+    fn test_snapshot_shared_across_threads() {
+        let snapshot = AAML::parse("greeting = hello").expect("Parsed").freeze();
+
+        let handles: Vec<_> = (0..4)
+            .map(|_| {
+                let snapshot = snapshot.clone();
+                thread::spawn(move || snapshot.find_obj_ref("greeting").map(str::to_string))
+            })
+            .collect();
+
+        for handle in handles {
+            assert_eq!(handle.join().unwrap().as_deref(), Some("hello"));
+        }
+        assert_eq!(AamlSnapshot::handle_count(&snapshot), 1);
+    }
+
+    #[test]
+    fn test_snapshot_clone_shares_data() {
+        let a = AAML::parse("k = v").expect("Parsed").freeze();
+        let b = a.clone();
+        assert!(AamlSnapshot::ptr_eq(&a, &b));
+        assert_eq!(AamlSnapshot::handle_count(&a), 2);
+    }
+}
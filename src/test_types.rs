@@ -0,0 +1,281 @@
+#[cfg(test)]
+mod tests {
+    use crate::aaml::AAML;
+    use crate::error::AamlError;
+    use crate::types::resolve_builtin;
+    use crate::value::{Bitrate, ByteSize, Color, Percentage};
+    use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+    fn valid(ty: &str, value: &str) -> bool {
+        resolve_builtin(ty).unwrap().validate(value).is_ok()
+    }
+
+    // ─────────────────────────────────────────────────────────────
+    //  Typed getters
+    // ─────────────────────────────────────────────────────────────
+
+    #[test]
+    fn test_get_as_primitives() {
+        let parser = AAML::parse("port = 8080\nratio = 0.5\ndebug = TRUE\nsep = ;\nname = app").unwrap();
+        assert_eq!(parser.get_as::<u16>("port").unwrap(), 8080);
+        assert_eq!(parser.get_as::<i64>("port").unwrap(), 8080);
+        assert_eq!(parser.get_as::<f64>("ratio").unwrap(), 0.5);
+        assert!(parser.get_as::<bool>("debug").unwrap());
+        assert_eq!(parser.get_as::<char>("sep").unwrap(), ';');
+        assert_eq!(parser.get_as::<String>("name").unwrap(), "app");
+
+        assert!(matches!(parser.get_as::<u8>("port"), Err(AamlError::InvalidValue(_))));
+        assert!(matches!(parser.get_as::<u16>("missing"), Err(AamlError::NotFound(_))));
+        // No reverse lookup: `app` is a value, not a key.
+        assert!(parser.get_as::<String>("app").is_err());
+    }
+
+    // ─────────────────────────────────────────────────────────────
+    //  data:: types
+    // ─────────────────────────────────────────────────────────────
+
+    #[test]
+    fn test_data_bytes() {
+        let content = "@type size = data::bytes\nmax_body = 10MiB\ncache = 512k\ndisk = 1.5 GB\nblock = 4096\nodd = 1.5B";
+        let parser = AAML::parse(content).unwrap();
+
+        assert_eq!(parser.get_as::<ByteSize>("max_body").unwrap(), ByteSize(10 * 1024 * 1024));
+        assert_eq!(parser.get_as::<ByteSize>("cache").unwrap(), ByteSize(512_000));
+        assert_eq!(parser.get_as::<ByteSize>("disk").unwrap(), ByteSize(1_500_000_000));
+        assert_eq!(parser.get_as::<ByteSize>("block").unwrap(), ByteSize(4096));
+        assert!(parser.get_as::<ByteSize>("odd").is_err());
+
+        assert!(parser.validate_value("size", "2 TiB").is_ok());
+        assert!(parser.validate_value("size", "16eib").is_err());
+        assert!(valid("data::bytes", "1kb") && valid("data::bytes", "0.5KiB"));
+        assert!(!valid("data::bytes", "-1k") && !valid("data::bytes", "10 MiBs") && !valid("data::bytes", "1.2.3k"));
+        assert!(!valid("data::bytes", "k") && !valid("data::bytes", "5 µB"));
+    }
+
+    #[test]
+    fn test_data_bitrate_and_percentage() {
+        let parser = AAML::parse("uplink = 100 Mbps\nserial = 9600\ndisk = 10 MB/s\nfiber = 2.5 Gbit/s\nload = 75%\nhalf = 0.5").unwrap();
+        assert_eq!(parser.get_as::<Bitrate>("uplink").unwrap(), Bitrate(100e6));
+        assert_eq!(parser.get_as::<Bitrate>("serial").unwrap(), Bitrate(9600.0));
+        assert_eq!(parser.get_as::<Bitrate>("disk").unwrap(), Bitrate(80e6));
+        assert_eq!(parser.get_as::<Bitrate>("fiber").unwrap(), Bitrate(2.5e9));
+        assert_eq!(parser.get_as::<Percentage>("load").unwrap(), Percentage(0.75));
+        assert_eq!(parser.get_as::<Percentage>("half").unwrap(), Percentage(0.5));
+
+        assert!(valid("data::bitrate", "1 kbps") && !valid("data::bitrate", "1 mbps") && !valid("data::bitrate", "fast"));
+        assert!(valid("data::percentage", "12.5 %") && !valid("data::percentage", "%") && !valid("data::percentage", "inf%"));
+        assert!(resolve_builtin("data::nope").is_err());
+
+        match resolve_builtin("data::bytes").unwrap().validate("10 XB") {
+            Err(AamlError::InvalidValue(msg)) => assert_eq!(msg, "Invalid bytes '10 XB': unknown unit 'xb'"),
+            other => panic!("Expected InvalidValue, got {other:?}"),
+        }
+    }
+
+    // ─────────────────────────────────────────────────────────────
+    //  time:: types
+    // ─────────────────────────────────────────────────────────────
+
+    #[test]
+    fn test_time_dates_and_datetimes() {
+        assert!(valid("time::date", "2024-02-29") && !valid("time::date", "2023-02-29"));
+        assert!(!valid("time::date", "2024-13-01") && !valid("time::date", "2024-04-31") && !valid("time::date", "2024-1-01"));
+        assert!(valid("time::datetime", "2024-01-15") && valid("time::datetime", "2024-01-15T10:30"));
+        assert!(valid("time::datetime", "2024-01-15T10:30:00.123456789Z") && valid("time::datetime", "2024-01-15 10:30:00+05:30"));
+        assert!(!valid("time::datetime", "2024-13-45") && !valid("time::datetime", "2024-01-15T24:00:00"));
+        assert!(!valid("time::datetime", "2024-01-15T10:30:00+25:00") && !valid("time::datetime", "2024-01-15T"));
+
+        assert!(valid("time::time", "23:59:59") && valid("time::time", "08:00Z") && valid("time::time", "08:00:00.5-03:00"));
+        assert!(!valid("time::time", "8:00") && !valid("time::time", "12:60") && !valid("time::time", "10:00.5"));
+        assert!(valid("time::timezone_offset", "Z") && valid("time::timezone_offset", "+05:30"));
+        assert!(valid("time::timezone_offset", "-0800") && valid("time::timezone_offset", "+09"));
+        assert!(!valid("time::timezone_offset", "05:30") && !valid("time::timezone_offset", "+5:30"));
+
+        match resolve_builtin("time::date").unwrap().validate("2023-02-29") {
+            Err(AamlError::InvalidValue(msg)) => assert!(msg.contains("day 29 does not exist in 2023-02"), "{msg}"),
+            other => panic!("Expected InvalidValue, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn test_time_durations() {
+        assert!(valid("time::duration", "90") && valid("time::duration", "1h30m") && valid("time::duration", "2d 12h"));
+        assert!(valid("time::duration", "P1Y2M10DT2H30M") && valid("time::duration", "PT0.5S") && valid("time::duration", "P2W"));
+        assert!(!valid("time::duration", "P") && !valid("time::duration", "PT") && !valid("time::duration", "P1H"));
+        assert!(!valid("time::duration", "PT1M1H") && !valid("time::duration", "P1.5DT1H") && !valid("time::duration", "Pxyz"));
+        assert!(!valid("time::duration", "1 fortnight") && !valid("time::duration", "-5") && !valid("time::duration", "h"));
+        assert!(valid("time::duration", "30 s") && valid("time::duration", "1 h 30 m") && !valid("time::duration", "1 h 30"));
+    }
+
+    #[test]
+    fn test_time_getters() {
+        let content = "timeout = 1h30m\npoll = 250ms\nspaced = 1 h 30 min\ngrace = PT1M30.5S\nidle = 45\nretention = P1Y\n\
+                       epoch = 1970-01-01\nlaunch = 2024-01-15T10:30:00+02:00\nancient = 1969-12-31T23:59:59Z";
+        let parser = AAML::parse(content).unwrap();
+
+        assert_eq!(parser.get_as::<Duration>("timeout").unwrap(), Duration::from_secs(5400));
+        assert_eq!(parser.get_as::<Duration>("poll").unwrap(), Duration::from_millis(250));
+        assert_eq!(parser.get_as::<Duration>("spaced").unwrap(), Duration::from_secs(5400));
+        assert_eq!(parser.get_as::<Duration>("grace").unwrap(), Duration::from_millis(90_500));
+        assert_eq!(parser.get_as::<Duration>("idle").unwrap(), Duration::from_secs(45));
+        assert!(parser.get_as::<Duration>("retention").is_err());
+
+        assert_eq!(parser.get_as::<SystemTime>("epoch").unwrap(), UNIX_EPOCH);
+        assert_eq!(parser.get_as::<SystemTime>("launch").unwrap(), UNIX_EPOCH + Duration::from_secs(1_705_307_400));
+        assert_eq!(parser.get_as::<SystemTime>("ancient").unwrap(), UNIX_EPOCH - Duration::from_secs(1));
+        assert!(parser.get_as::<SystemTime>("timeout").is_err());
+
+        // Representable on Unix; an error instead of a panic where the clock starts later.
+        let parser = AAML::parse("first = 0001-01-01\nlast = 9999-12-31T23:59:59.999999999Z").unwrap();
+        for (key, expected) in [("first", true), ("last", false)] {
+            match parser.get_as::<SystemTime>(key) {
+                Ok(instant) => assert_eq!(instant < UNIX_EPOCH, expected),
+                Err(e) => assert!(matches!(e, AamlError::InvalidValue(_)), "{e:?}"),
+            }
+        }
+    }
+
+    // ─────────────────────────────────────────────────────────────
+    //  physics:: types
+    // ─────────────────────────────────────────────────────────────
+
+    fn close(a: f64, b: f64) -> bool {
+        (a - b).abs() <= 1e-9 * b.abs().max(1.0)
+    }
+
+    #[test]
+    fn test_physics_units_and_dimensions() {
+        assert!(valid("physics::meter", "12.5") && valid("physics::meter", "3 ft") && valid("physics::meter", "5km"));
+        assert!(valid("physics::meter", "5 nauticalMile") && valid("physics::meter", "2 kilometers") && valid("physics::meter", "1 Å"));
+        assert!(valid("physics::meterPerSecond", "30 km/h") && valid("physics::meterPerSecond", "12 knots"));
+        assert!(valid("physics::newton", "3 kg*m/s^2") && valid("physics::wattPerMeterKelvin", "0.6 W/(m·K)"));
+        assert!(valid("physics::joule", "1.5 kWh") && valid("physics::joule", "10eV") && valid("physics::pascal", "1013 hPa"));
+
+        assert!(!valid("physics::meter", "3 kg") && !valid("physics::meter", "3 m^2") && !valid("physics::meter", "3 furlongs"));
+        assert!(valid("physics::radian", "90 deg") && !valid("physics::radian", "2 m") && !valid("physics::dimensionless", "2 rad"));
+        assert!(!valid("physics::byte", "1.5") && valid("physics::byte", "1.5 kB") && !valid("physics::second", "fast"));
+        assert!(valid("physics::decibel", "3 dB") && !valid("physics::percentage", "3 dB") && !valid("physics::dimensionless", "3 dB"));
+        assert!(!valid("physics::decibel", "3 %") && !valid("physics::metabolicEquivalent", "3 dB") && valid("physics::percentage", "0.5"));
+
+        match resolve_builtin("physics::meter").unwrap().validate("3 kg") {
+            Err(AamlError::InvalidValue(msg)) => {
+                assert_eq!(msg, "Invalid meter '3 kg': kg (kg) is not compatible with meter (m)")
+            }
+            other => panic!("Expected InvalidValue, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn test_physics_get_quantity() {
+        let content = "@type speed = physics::meterPerSecond\n@type limit = physics::kilogram where 0..=100\n\
+                       cruise = 36 km/h\nwind = 10\nboat = 10 kn\nheight = 6 ft\nroom = 20 °C\noven = 392 degF\n\
+                       load = 50 lb\nheavy = 1 t\ndistance = 5 nauticalMile";
+        let parser = AAML::parse(content).unwrap();
+
+        assert!(close(parser.get_quantity("cruise", "speed").unwrap(), 10.0));
+        assert!(close(parser.get_quantity("wind", "speed").unwrap(), 10.0));
+        assert!(close(parser.get_quantity("boat", "speed").unwrap(), 18.52 / 3.6));
+        assert!(close(parser.get_quantity("height", "physics::meter").unwrap(), 1.8288));
+        assert!(close(parser.get_quantity("room", "physics::kelvin").unwrap(), 293.15));
+        assert!(close(parser.get_quantity("oven", "physics::celsius").unwrap(), 200.0));
+        assert!(close(parser.get_quantity("distance", "physics::meter").unwrap(), 9260.0));
+
+        // Ranges on refined types compare the converted quantity.
+        assert!(close(parser.get_quantity("load", "limit").unwrap(), 22.679_618_5));
+        assert!(matches!(parser.get_quantity("heavy", "limit"), Err(AamlError::InvalidType { .. })));
+        assert!(parser.validate_value("limit", "300 lb").is_err() && parser.validate_value("limit", "150 g").is_ok());
+
+        assert!(matches!(parser.get_quantity("height", "speed"), Err(AamlError::InvalidType { .. })));
+        assert!(matches!(parser.get_quantity("missing", "speed"), Err(AamlError::NotFound(_))));
+    }
+
+    // ─────────────────────────────────────────────────────────────
+    //  color
+    // ─────────────────────────────────────────────────────────────
+
+    #[test]
+    fn test_color_syntaxes() {
+        assert!(valid("color", "#fff") && valid("color", "#FFF8") && valid("color", "#ff8800") && valid("color", "#ff880080"));
+        assert!(valid("color", "RebeccaPurple") && valid("color", "transparent"));
+        assert!(valid("color", "rgb(255, 0, 0)") && valid("color", "rgba(0 128 255 / 50%)") && valid("color", "rgb(100%, 0%, 0%)"));
+        assert!(valid("color", "hsl(120deg 100% 50%)") && valid("color", "hsla(240, 100%, 50%, 0.25)"));
+
+        assert!(!valid("color", "#+fffff") && !valid("color", "#fffff") && !valid("color", "#1234567"));
+        assert!(!valid("color", "#ggg") && !valid("color", "blurple") && !valid("color", "rgb(256, 0, 0)"));
+        assert!(!valid("color", "rgb(1, 2)") && !valid("color", "rgb(1, 2, 3") && !valid("color", "hsl(120, 100, 50)"));
+        assert!(!valid("color", "rgba(0, 0, 0, 1.5)") && !valid("color", "cmyk(0, 0, 0, 0)"));
+
+        match resolve_builtin("color").unwrap().validate("#12345") {
+            Err(AamlError::InvalidValue(msg)) => assert_eq!(msg, "Invalid color '#12345': expected 3, 4, 6 or 8 hex digits, got 5"),
+            other => panic!("Expected InvalidValue, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn test_color_getter() {
+        let content = "accent = \"#f80\"\nbg = \"#1e1e1e\"\noverlay = \"#00000080\"\nlink = SteelBlue\nwarn = rgb(255, 165, 0)\n\
+                       glass = rgba(255 255 255 / 25%)\nok = hsl(120, 100%, 25%)\nnone = transparent";
+        let parser = AAML::parse(content).unwrap();
+
+        assert_eq!(parser.get_as::<Color>("accent").unwrap(), Color { r: 255, g: 136, b: 0, a: 255 });
+        assert_eq!(parser.get_as::<Color>("bg").unwrap(), Color { r: 30, g: 30, b: 30, a: 255 });
+        assert_eq!(parser.get_as::<Color>("overlay").unwrap(), Color { r: 0, g: 0, b: 0, a: 128 });
+        assert_eq!(parser.get_as::<Color>("link").unwrap(), Color { r: 70, g: 130, b: 180, a: 255 });
+        assert_eq!(parser.get_as::<Color>("warn").unwrap(), Color { r: 255, g: 165, b: 0, a: 255 });
+        assert_eq!(parser.get_as::<Color>("glass").unwrap(), Color { r: 255, g: 255, b: 255, a: 64 });
+        assert_eq!(parser.get_as::<Color>("ok").unwrap(), Color { r: 0, g: 128, b: 0, a: 255 });
+        assert_eq!(parser.get_as::<Color>("none").unwrap().a, 0);
+
+        assert_eq!(parser.get_as::<Color>("accent").unwrap().to_string(), "#ff8800");
+        assert_eq!(parser.get_as::<Color>("overlay").unwrap().to_string(), "#00000080");
+    }
+
+    // ─────────────────────────────────────────────────────────────
+    //  math:: types
+    // ─────────────────────────────────────────────────────────────
+
+    #[test]
+    fn test_math_syntaxes() {
+        assert!(valid("math::vector3", "1, 2, 3") && valid("math::vector3", "(1, 2, 3)") && valid("math::vector3", "[1, 2, 3]"));
+        assert!(valid("math::matrix3x3", "1, 0, 0, 0, 1, 0, 0, 0, 1") && valid("math::matrix3x3", "[[1, 2, 3], [4, 5, 6], [7, 8, 9]]"));
+        assert!(!valid("math::vector3", "(1, 2, 3") && !valid("math::vector3", "[[1, 2, 3]]") && !valid("math::vector3", "1, 2, NaN"));
+        assert!(!valid("math::matrix3x3", "[[1, 2, 3], [4, 5, 6]]") && !valid("math::matrix3x3", "[[1, 2], [3, 4, 5, 6], [7, 8, 9]]"));
+        assert!(!valid("math::matrix3x3", "[[1, 2, 3], [4, 5, 6], [7, 8, 9],]") && !valid("math::matrix3x3", "[[1, 2, 3] [4, 5, 6] [7, 8, 9]]"));
+
+        match resolve_builtin("math::matrix3x3").unwrap().validate("[[1, 2, 3], [4, 5], [7, 8, 9]]") {
+            Err(AamlError::InvalidValue(msg)) => assert_eq!(msg, "Expected 3 components in row 2, got 2"),
+            other => panic!("Expected InvalidValue, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn test_math_geometry() {
+        assert!(valid("math::unit_vector3", "0, 0, 1") && valid("math::unit_vector3", "(0.6, 0.8, 0)"));
+        assert!(!valid("math::unit_vector3", "1, 1, 0") && !valid("math::unit_vector3", "0, 0, 0"));
+        assert!(valid("math::unit_quaternion", "0, 0, 0, 1") && valid("math::unit_quaternion", "0.5, 0.5, 0.5, 0.5"));
+        assert!(valid("math::unit_quaternion", "0, 0, 0.7071068, 0.7071068") && !valid("math::unit_quaternion", "0, 0, 0.7, 0.7"));
+
+        assert!(valid("math::rotation_matrix3x3", "[[1, 0, 0], [0, 1, 0], [0, 0, 1]]"));
+        assert!(valid("math::rotation_matrix3x3", "[[0, -1, 0], [1, 0, 0], [0, 0, 1]]"));
+        assert!(!valid("math::rotation_matrix3x3", "[[2, 0, 0], [0, 1, 0], [0, 0, 1]]"));
+        match resolve_builtin("math::rotation_matrix3x3").unwrap().validate("[[1, 0, 0], [0, 1, 0], [0, 0, -1]]") {
+            Err(AamlError::InvalidValue(msg)) => assert_eq!(msg, "Expected a rotation, but the determinant is -1"),
+            other => panic!("Expected InvalidValue, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn test_math_getters() {
+        let content = "@type up = math::unit_vector3\nposition = (1.5, -2, 3)\nforward = [0, 0, 1]\n\
+                       rotation = [[0, -1, 0], [1, 0, 0], [0, 0, 1]]\nscale = 2, 0, 0, 2";
+        let parser = AAML::parse(content).unwrap();
+
+        assert_eq!(parser.get_as::<[f64; 3]>("position").unwrap(), [1.5, -2.0, 3.0]);
+        assert_eq!(parser.get_as::<[f64; 3]>("forward").unwrap(), [0.0, 0.0, 1.0]);
+        assert_eq!(parser.get_as::<[[f64; 3]; 3]>("rotation").unwrap()[1], [1.0, 0.0, 0.0]);
+        assert_eq!(parser.get_as::<[[f64; 2]; 2]>("scale").unwrap(), [[2.0, 0.0], [0.0, 2.0]]);
+        assert!(parser.get_as::<[f64; 4]>("position").is_err() && parser.get_as::<[f64; 9]>("rotation").is_err());
+
+        assert!(parser.validate_value("up", "0, 1, 0").is_ok() && parser.validate_value("up", "0, 2, 0").is_err());
+    }
+}
@@ -0,0 +1,170 @@
+//! Parsing for the `color` primitive: hex (`#RGB`, `#RGBA`, `#RRGGBB`, `#RRGGBBAA`), CSS
+//! named colors and the `rgb()`/`rgba()`/`hsl()`/`hsla()` functions.
+
+use crate::value::Color;
+
+pub(crate) fn parse_color(value: &str) -> Result<Color, String> {
+    let value = value.trim();
+    if let Some(hex) = value.strip_prefix('#') {
+        return parse_hex(hex);
+    }
+    if let Some(open) = value.find('(') {
+        let function = value[..open].trim().to_ascii_lowercase();
+        let args = value[open + 1..]
+            .strip_suffix(')')
+            .ok_or_else(|| format!("missing ')' after {}(", function))?;
+        return match function.as_str() {
+            "rgb" | "rgba" => parse_rgb(args),
+            "hsl" | "hsla" => parse_hsl(args),
+            _ => Err(format!("unknown color function '{}'", function)),
+        };
+    }
+    named(&value.to_ascii_lowercase()).ok_or_else(|| format!("unknown color name '{}'", value))
+}
+
+fn parse_hex(hex: &str) -> Result<Color, String> {
+    if !hex.chars().all(|c| c.is_ascii_hexdigit()) {
+        return Err(format!("'#{}' contains non-hex digits", hex));
+    }
+    // Only ASCII hex digits remain, so byte indexing is safe.
+    let digit = |i: usize| u8::from_str_radix(&hex[i..=i], 16).unwrap_or(0);
+    let pair = |i: usize| u8::from_str_radix(&hex[i..i + 2], 16).unwrap_or(0);
+    match hex.len() {
+        3 | 4 => {
+            let short = |i: usize| digit(i) * 17;
+            let a = if hex.len() == 4 { short(3) } else { 255 };
+            Ok(Color { r: short(0), g: short(1), b: short(2), a })
+        }
+        6 | 8 => {
+            let a = if hex.len() == 8 { pair(6) } else { 255 };
+            Ok(Color { r: pair(0), g: pair(2), b: pair(4), a })
+        }
+        n => Err(format!("expected 3, 4, 6 or 8 hex digits, got {}", n)),
+    }
+}
+
+/// Splits `255, 0, 0, 0.5` or the modern `255 0 0 / 50%` into the channels and the
+/// optional alpha.
+fn split_args(args: &str) -> Result<(Vec<&str>, Option<&str>), String> {
+    let (channels, alpha): (Vec<&str>, Option<&str>) = if args.contains(',') {
+        let mut parts: Vec<&str> = args.split(',').map(str::trim).collect();
+        let alpha = if parts.len() == 4 { parts.pop() } else { None };
+        (parts, alpha)
+    } else {
+        let (channels, alpha) = match args.split_once('/') {
+            Some((channels, alpha)) => (channels, Some(alpha.trim())),
+            None => (args, None),
+        };
+        (channels.split_whitespace().collect(), alpha)
+    };
+    if channels.len() != 3 {
+        return Err(format!("expected 3 components and an optional alpha, got '{}'", args.trim()));
+    }
+    Ok((channels, alpha))
+}
+
+/// A number, or a percentage of `full`; either must lie within `0..=full`.
+fn component(s: &str, full: f64) -> Result<f64, String> {
+    let (number, scale) = match s.strip_suffix('%') {
+        Some(percent) => (percent, full / 100.0),
+        None => (s, 1.0),
+    };
+    let n = number
+        .trim()
+        .parse::<f64>()
+        .ok()
+        .filter(|n| n.is_finite())
+        .ok_or_else(|| format!("'{}' is not a number", s))?
+        * scale;
+    if (0.0..=full).contains(&n) {
+        Ok(n)
+    } else {
+        Err(format!("'{}' is outside 0..={}", s, full))
+    }
+}
+
+fn alpha(s: Option<&str>) -> Result<u8, String> {
+    s.map_or(Ok(255), |s| component(s, 1.0).map(|a| (a * 255.0).round() as u8))
+}
+
+fn parse_rgb(args: &str) -> Result<Color, String> {
+    let (channels, a) = split_args(args)?;
+    let channel = |i: usize| component(channels[i], 255.0).map(|c| c.round() as u8);
+    Ok(Color { r: channel(0)?, g: channel(1)?, b: channel(2)?, a: alpha(a)? })
+}
+
+fn parse_hsl(args: &str) -> Result<Color, String> {
+    let (channels, a) = split_args(args)?;
+    let hue = channels[0];
+    let hue = hue
+        .strip_suffix("deg")
+        .unwrap_or(hue)
+        .trim()
+        .parse::<f64>()
+        .ok()
+        .filter(|h| h.is_finite())
+        .ok_or_else(|| format!("'{}' is not a hue", hue))?
+        .rem_euclid(360.0);
+    let percent = |s: &str| match s.strip_suffix('%') {
+        Some(_) => component(s, 1.0),
+        None => Err(format!("'{}' must be a percentage", s)),
+    };
+    let (s, l) = (percent(channels[1])?, percent(channels[2])?);
+
+    // CSS Color 4, section 7.1.
+    let f = |n: f64| {
+        let k = (n + hue / 30.0) % 12.0;
+        let v = l - s * l.min(1.0 - l) * (k - 3.0).min(9.0 - k).clamp(-1.0, 1.0);
+        (v * 255.0).round() as u8
+    };
+    Ok(Color { r: f(0.0), g: f(8.0), b: f(4.0), a: alpha(a)? })
+}
+
+fn named(name: &str) -> Option<Color> {
+    if name == "transparent" {
+        return Some(Color { r: 0, g: 0, b: 0, a: 0 });
+    }
+    let rgb = NAMED_COLORS.iter().find(|(n, _)| *n == name)?.1;
+    Some(Color { r: (rgb >> 16) as u8, g: (rgb >> 8) as u8, b: rgb as u8, a: 255 })
+}
+
+/// The CSS Color Module Level 4 named colors.
+const NAMED_COLORS: [(&str, u32); 148] = [
+    ("aliceblue", 0xF0F8FF), ("antiquewhite", 0xFAEBD7), ("aqua", 0x00FFFF), ("aquamarine", 0x7FFFD4),
+    ("azure", 0xF0FFFF), ("beige", 0xF5F5DC), ("bisque", 0xFFE4C4), ("black", 0x000000),
+    ("blanchedalmond", 0xFFEBCD), ("blue", 0x0000FF), ("blueviolet", 0x8A2BE2), ("brown", 0xA52A2A),
+    ("burlywood", 0xDEB887), ("cadetblue", 0x5F9EA0), ("chartreuse", 0x7FFF00), ("chocolate", 0xD2691E),
+    ("coral", 0xFF7F50), ("cornflowerblue", 0x6495ED), ("cornsilk", 0xFFF8DC), ("crimson", 0xDC143C),
+    ("cyan", 0x00FFFF), ("darkblue", 0x00008B), ("darkcyan", 0x008B8B), ("darkgoldenrod", 0xB8860B),
+    ("darkgray", 0xA9A9A9), ("darkgreen", 0x006400), ("darkgrey", 0xA9A9A9), ("darkkhaki", 0xBDB76B),
+    ("darkmagenta", 0x8B008B), ("darkolivegreen", 0x556B2F), ("darkorange", 0xFF8C00), ("darkorchid", 0x9932CC),
+    ("darkred", 0x8B0000), ("darksalmon", 0xE9967A), ("darkseagreen", 0x8FBC8F), ("darkslateblue", 0x483D8B),
+    ("darkslategray", 0x2F4F4F), ("darkslategrey", 0x2F4F4F), ("darkturquoise", 0x00CED1), ("darkviolet", 0x9400D3),
+    ("deeppink", 0xFF1493), ("deepskyblue", 0x00BFFF), ("dimgray", 0x696969), ("dimgrey", 0x696969),
+    ("dodgerblue", 0x1E90FF), ("firebrick", 0xB22222), ("floralwhite", 0xFFFAF0), ("forestgreen", 0x228B22),
+    ("fuchsia", 0xFF00FF), ("gainsboro", 0xDCDCDC), ("ghostwhite", 0xF8F8FF), ("gold", 0xFFD700),
+    ("goldenrod", 0xDAA520), ("gray", 0x808080), ("green", 0x008000), ("greenyellow", 0xADFF2F),
+    ("grey", 0x808080), ("honeydew", 0xF0FFF0), ("hotpink", 0xFF69B4), ("indianred", 0xCD5C5C),
+    ("indigo", 0x4B0082), ("ivory", 0xFFFFF0), ("khaki", 0xF0E68C), ("lavender", 0xE6E6FA),
+    ("lavenderblush", 0xFFF0F5), ("lawngreen", 0x7CFC00), ("lemonchiffon", 0xFFFACD), ("lightblue", 0xADD8E6),
+    ("lightcoral", 0xF08080), ("lightcyan", 0xE0FFFF), ("lightgoldenrodyellow", 0xFAFAD2), ("lightgray", 0xD3D3D3),
+    ("lightgreen", 0x90EE90), ("lightgrey", 0xD3D3D3), ("lightpink", 0xFFB6C1), ("lightsalmon", 0xFFA07A),
+    ("lightseagreen", 0x20B2AA), ("lightskyblue", 0x87CEFA), ("lightslategray", 0x778899), ("lightslategrey", 0x778899),
+    ("lightsteelblue", 0xB0C4DE), ("lightyellow", 0xFFFFE0), ("lime", 0x00FF00), ("limegreen", 0x32CD32),
+    ("linen", 0xFAF0E6), ("magenta", 0xFF00FF), ("maroon", 0x800000), ("mediumaquamarine", 0x66CDAA),
+    ("mediumblue", 0x0000CD), ("mediumorchid", 0xBA55D3), ("mediumpurple", 0x9370DB), ("mediumseagreen", 0x3CB371),
+    ("mediumslateblue", 0x7B68EE), ("mediumspringgreen", 0x00FA9A), ("mediumturquoise", 0x48D1CC), ("mediumvioletred", 0xC71585),
+    ("midnightblue", 0x191970), ("mintcream", 0xF5FFFA), ("mistyrose", 0xFFE4E1), ("moccasin", 0xFFE4B5),
+    ("navajowhite", 0xFFDEAD), ("navy", 0x000080), ("oldlace", 0xFDF5E6), ("olive", 0x808000),
+    ("olivedrab", 0x6B8E23), ("orange", 0xFFA500), ("orangered", 0xFF4500), ("orchid", 0xDA70D6),
+    ("palegoldenrod", 0xEEE8AA), ("palegreen", 0x98FB98), ("paleturquoise", 0xAFEEEE), ("palevioletred", 0xDB7093),
+    ("papayawhip", 0xFFEFD5), ("peachpuff", 0xFFDAB9), ("peru", 0xCD853F), ("pink", 0xFFC0CB),
+    ("plum", 0xDDA0DD), ("powderblue", 0xB0E0E6), ("purple", 0x800080), ("rebeccapurple", 0x663399),
+    ("red", 0xFF0000), ("rosybrown", 0xBC8F8F), ("royalblue", 0x4169E1), ("saddlebrown", 0x8B4513),
+    ("salmon", 0xFA8072), ("sandybrown", 0xF4A460), ("seagreen", 0x2E8B57), ("seashell", 0xFFF5EE),
+    ("sienna", 0xA0522D), ("silver", 0xC0C0C0), ("skyblue", 0x87CEEB), ("slateblue", 0x6A5ACD),
+    ("slategray", 0x708090), ("slategrey", 0x708090), ("snow", 0xFFFAFA), ("springgreen", 0x00FF7F),
+    ("steelblue", 0x4682B4), ("tan", 0xD2B48C), ("teal", 0x008080), ("thistle", 0xD8BFD8),
+    ("tomato", 0xFF6347), ("turquoise", 0x40E0D0), ("violet", 0xEE82EE), ("wheat", 0xF5DEB3),
+    ("white", 0xFFFFFF), ("whitesmoke", 0xF5F5F5), ("yellow", 0xFFFF00), ("yellowgreen", 0x9ACD32),
+];
@@ -0,0 +1,148 @@
+use std::fmt;
+use crate::error::AamlError;
+use crate::types::primitive_type::PrimitiveType;
+use crate::types::Type;
+
+pub enum DataTypes {
+    /// `512`, `512k`, `10MiB`, `1.5 GB`: a whole number of bytes.
+    Bytes,
+    /// `9600`, `100 Mbps`, `2.5 Gbit/s`, `10 MB/s`: bits per second.
+    Bitrate,
+    /// `50%`, `12.5 %` or a plain fraction such as `0.25`.
+    Percentage,
+}
+
+impl Type for DataTypes {
+    fn from_name(name: &str) -> Result<Self, AamlError>
+    where
+        Self: Sized
+    {
+        match name {
+            "bytes" => Ok(DataTypes::Bytes),
+            "bitrate" => Ok(DataTypes::Bitrate),
+            "percentage" => Ok(DataTypes::Percentage),
+            _ => Err(AamlError::NotFound(name.to_string())),
+        }
+    }
+
+    fn base_type(&self) -> PrimitiveType {
+        match self {
+            DataTypes::Bytes => PrimitiveType::U64,
+            DataTypes::Bitrate | DataTypes::Percentage => PrimitiveType::F64,
+        }
+    }
+
+    fn validate(&self, value: &str) -> Result<(), AamlError> {
+        self.quantity(value).map(|_| ())
+    }
+
+    /// Bytes, bits per second, or the fraction for percentages.
+    fn quantity(&self, value: &str) -> Result<f64, AamlError> {
+        let result = match self {
+            DataTypes::Bytes => parse_bytes(value).map(|n| n as f64),
+            DataTypes::Bitrate => parse_bitrate(value),
+            DataTypes::Percentage => parse_percentage(value),
+        };
+        result.map_err(|details| AamlError::InvalidValue(format!("Invalid {} '{}': {}", self, value, details)))
+    }
+}
+
+impl fmt::Display for DataTypes {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let s = match self {
+            DataTypes::Bytes => "bytes",
+            DataTypes::Bitrate => "bitrate",
+            DataTypes::Percentage => "percentage",
+        };
+        write!(f, "{}", s)
+    }
+}
+
+/// Splits `10 MiB` into the number and the (trimmed) suffix.
+fn split_number(value: &str) -> Result<(&str, &str), String> {
+    let value = value.trim();
+    let end = value
+        .find(|c: char| !(c.is_ascii_digit() || c == '.'))
+        .unwrap_or(value.len());
+    let (number, suffix) = value.split_at(end);
+    if number.is_empty() || number.starts_with('.') || number.ends_with('.') || number.matches('.').count() > 1 {
+        return Err("expected a number".into());
+    }
+    Ok((number, suffix.trim_start()))
+}
+
+/// Byte count of `512`, `64k`, `10MiB`, `1.5 GB`. SI prefixes (`k`, `M`, `G`, `T`, `P`,
+/// `E`) are powers of 1000 and IEC prefixes (`Ki`, `Mi`, ...) powers of 1024; the trailing
+/// `B` is optional and case is ignored. Fractions are exact and must come out as whole bytes.
+pub(crate) fn parse_bytes(value: &str) -> Result<u64, String> {
+    let (number, suffix) = split_number(value)?;
+    let suffix = suffix.to_ascii_lowercase();
+    let unit = suffix.strip_suffix('b').unwrap_or(&suffix);
+
+    let (base, power): (u128, u32) = match unit.as_bytes() {
+        [] => (1, 0),
+        [prefix] => (1000, prefix_power(*prefix).ok_or_else(|| format!("unknown unit '{}'", suffix))?),
+        [prefix, b'i'] => (1024, prefix_power(*prefix).ok_or_else(|| format!("unknown unit '{}'", suffix))?),
+        _ => return Err(format!("unknown unit '{}'", suffix)),
+    };
+    let multiplier = base.pow(power);
+
+    // 1.5 -> 15 / 10, so the product stays exact.
+    let (whole, fraction) = number.split_once('.').unwrap_or((number, ""));
+    let digits: u128 = format!("{}{}", whole, fraction).parse().map_err(|_| "number is too large".to_string())?;
+    let divisor = 10u128.checked_pow(fraction.len() as u32).ok_or("too many decimal places")?;
+    let scaled = digits.checked_mul(multiplier).ok_or("number is too large")?;
+
+    if scaled % divisor != 0 {
+        return Err("not a whole number of bytes".into());
+    }
+    u64::try_from(scaled / divisor).map_err(|_| "number is too large".into())
+}
+
+fn prefix_power(prefix: u8) -> Option<u32> {
+    match prefix {
+        b'k' => Some(1),
+        b'm' => Some(2),
+        b'g' => Some(3),
+        b't' => Some(4),
+        b'p' => Some(5),
+        b'e' => Some(6),
+        _ => None,
+    }
+}
+
+/// Bits per second of `9600`, `100 Mbps`, `2.5 Gbit/s`, `10 MB/s`. `b`/`bit` are bits and
+/// `B` bytes; prefixes `k`/`K`, `M`, `G`, `T` are powers of 1000.
+pub(crate) fn parse_bitrate(value: &str) -> Result<f64, String> {
+    let (number, suffix) = split_number(value)?;
+    let number: f64 = number.parse().map_err(|_| "expected a number".to_string())?;
+    if suffix.is_empty() {
+        return Ok(number);
+    }
+
+    let prefixes: [(&[char], f64); 4] = [(&['k', 'K'], 1e3), (&['M'], 1e6), (&['G'], 1e9), (&['T'], 1e12)];
+    let (scale, unit) = prefixes
+        .iter()
+        .find_map(|&(prefix, scale)| suffix.strip_prefix(prefix).map(|unit| (scale, unit)))
+        .unwrap_or((1.0, suffix));
+    let bits = match unit {
+        "bps" | "b/s" | "bit/s" => 1.0,
+        "Bps" | "B/s" => 8.0,
+        _ => return Err(format!("unknown unit '{}'", suffix)),
+    };
+    Ok(number * scale * bits)
+}
+
+/// `50%` -> 0.5; a number without `%` is taken as the fraction itself.
+pub(crate) fn parse_percentage(value: &str) -> Result<f64, String> {
+    let value = value.trim();
+    let (number, scale) = match value.strip_suffix('%') {
+        Some(number) => (number.trim_end(), 100.0),
+        None => (value, 1.0),
+    };
+    let number: f64 = number.parse().map_err(|_| "expected a number or a percentage like 50%".to_string())?;
+    if !number.is_finite() {
+        return Err("expected a finite number".into());
+    }
+    Ok(number / scale)
+}
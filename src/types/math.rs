@@ -1,6 +1,9 @@
 use crate::error::AamlError;
 use crate::types::Type;
 
+/// How far a norm, dot product or determinant may stray from its ideal value.
+const TOLERANCE: f64 = 1e-6;
+
 pub enum MathTypes {
     Vector2,
     Vector3,
@@ -8,6 +11,12 @@ pub enum MathTypes {
     Quaternion,
     Matrix3x3,
     Matrix4x4,
+    /// A vector3 of length 1.
+    UnitVector3,
+    /// A quaternion of norm 1, i.e. a rotation.
+    UnitQuaternion,
+    /// An orthonormal matrix3x3 with determinant +1.
+    RotationMatrix3x3,
 }
 
 impl Type for MathTypes {
@@ -22,6 +31,9 @@ impl Type for MathTypes {
             "quaternion" => Ok(MathTypes::Quaternion),
             "matrix3x3" => Ok(MathTypes::Matrix3x3),
             "matrix4x4" => Ok(MathTypes::Matrix4x4),
+            "unit_vector3" => Ok(MathTypes::UnitVector3),
+            "unit_quaternion" => Ok(MathTypes::UnitQuaternion),
+            "rotation_matrix3x3" => Ok(MathTypes::RotationMatrix3x3),
             _ => Err(crate::error::AamlError::NotFound(name.to_string())),
         }
     }
@@ -31,25 +43,127 @@ impl Type for MathTypes {
     }
 
     fn validate(&self, value: &str) -> Result<(), AamlError> {
-        let parts: Vec<&str> = value.split(',').map(|s| s.trim()).collect();
-        let expected_len = match self {
-            MathTypes::Vector2 => 2,
-            MathTypes::Vector3 => 3,
-            MathTypes::Vector4 | MathTypes::Quaternion => 4,
-            MathTypes::Matrix3x3 => 9,
-            MathTypes::Matrix4x4 => 16,
-        };
-
-        if parts.len() != expected_len {
-            return Err(AamlError::InvalidValue(format!("Expected {} components, got {}", expected_len, parts.len())));
-        }
-
-        for part in parts {
-            if part.parse::<f64>().is_err() {
-                return Err(AamlError::InvalidValue(format!("Invalid number: {}", part)));
+        let (rows, cols) = self.shape();
+        let values = parse_matrix(value, rows, cols).map_err(AamlError::InvalidValue)?;
+
+        match self {
+            MathTypes::UnitVector3 | MathTypes::UnitQuaternion => {
+                let norm = dot(&values, &values).sqrt();
+                if (norm - 1.0).abs() > TOLERANCE {
+                    return Err(AamlError::InvalidValue(format!("Expected a norm of 1, got {}", norm)));
+                }
             }
+            MathTypes::RotationMatrix3x3 => check_rotation(&values).map_err(AamlError::InvalidValue)?,
+            _ => {}
         }
-
         Ok(())
     }
-}
\ No newline at end of file
+}
+
+impl MathTypes {
+    /// Rows and columns; vectors and quaternions are a single row.
+    fn shape(&self) -> (usize, usize) {
+        match self {
+            MathTypes::Vector2 => (1, 2),
+            MathTypes::Vector3 | MathTypes::UnitVector3 => (1, 3),
+            MathTypes::Vector4 | MathTypes::Quaternion | MathTypes::UnitQuaternion => (1, 4),
+            MathTypes::Matrix3x3 | MathTypes::RotationMatrix3x3 => (3, 3),
+            MathTypes::Matrix4x4 => (4, 4),
+        }
+    }
+}
+
+fn dot(a: &[f64], b: &[f64]) -> f64 {
+    a.iter().zip(b).map(|(x, y)| x * y).sum()
+}
+
+/// The rows of a row-major 3x3 matrix must be orthonormal and its determinant +1, which
+/// rules out reflections.
+fn check_rotation(m: &[f64]) -> Result<(), String> {
+    let row = |i: usize| &m[i * 3..i * 3 + 3];
+    for i in 0..3 {
+        for j in i..3 {
+            let expected = if i == j { 1.0 } else { 0.0 };
+            let product = dot(row(i), row(j));
+            if (product - expected).abs() > TOLERANCE {
+                return Err(format!(
+                    "Expected an orthonormal matrix, but row {} · row {} = {}", i + 1, j + 1, product
+                ));
+            }
+        }
+    }
+    let det = m[0] * (m[4] * m[8] - m[5] * m[7]) - m[1] * (m[3] * m[8] - m[5] * m[6])
+        + m[2] * (m[3] * m[7] - m[4] * m[6]);
+    if (det - 1.0).abs() > TOLERANCE {
+        return Err(format!("Expected a rotation, but the determinant is {}", det));
+    }
+    Ok(())
+}
+
+/// Parses `1, 2, 3`, `(1, 2, 3)` or `[1, 2, 3]` into one row, and the row-wise
+/// `[[1, 0], [0, 1]]` into one row per bracket group.
+fn parse_rows(value: &str) -> Result<Vec<Vec<f64>>, String> {
+    let value = value.trim();
+    let inner = value
+        .strip_prefix('[')
+        .and_then(|v| v.strip_suffix(']'))
+        .or_else(|| value.strip_prefix('(').and_then(|v| v.strip_suffix(')')))
+        .unwrap_or(value)
+        .trim();
+    if !inner.starts_with('[') {
+        return Ok(vec![parse_row(inner)?]);
+    }
+
+    let mut rows = Vec::new();
+    let mut rest = inner;
+    while !rest.is_empty() {
+        let row = rest.strip_prefix('[').ok_or_else(|| format!("Expected '[' to start row {}", rows.len() + 1))?;
+        let end = row.find(']').ok_or_else(|| format!("Missing ']' after row {}", rows.len() + 1))?;
+        rows.push(parse_row(&row[..end])?);
+        rest = row[end + 1..].trim_start();
+        if let Some(next) = rest.strip_prefix(',') {
+            rest = next.trim_start();
+            if rest.is_empty() {
+                return Err("Trailing ',' after the last row".to_string());
+            }
+        } else if !rest.is_empty() {
+            return Err(format!("Expected ',' between rows, got '{}'", rest));
+        }
+    }
+    Ok(rows)
+}
+
+fn parse_row(row: &str) -> Result<Vec<f64>, String> {
+    row.split(',')
+        .map(str::trim)
+        .map(|part| {
+            part.parse::<f64>()
+                .ok()
+                .filter(|n| n.is_finite())
+                .ok_or_else(|| format!("Invalid number: {}", part))
+        })
+        .collect()
+}
+
+/// Parses a `rows` x `cols` value into its components in row-major order. Flat syntaxes
+/// list all components; row-wise notation must have exactly `rows` rows of `cols`.
+pub(crate) fn parse_matrix(value: &str, rows: usize, cols: usize) -> Result<Vec<f64>, String> {
+    let parsed = parse_rows(value)?;
+    if parsed.len() > 1 || value.trim().starts_with("[[") {
+        if rows == 1 {
+            return Err(format!("Expected {} components, not rows", cols));
+        }
+        if parsed.len() != rows {
+            return Err(format!("Expected {} rows, got {}", rows, parsed.len()));
+        }
+        if let Some((i, row)) = parsed.iter().enumerate().find(|(_, row)| row.len() != cols) {
+            return Err(format!("Expected {} components in row {}, got {}", cols, i + 1, row.len()));
+        }
+    }
+
+    let values: Vec<f64> = parsed.into_iter().flatten().collect();
+    if values.len() != rows * cols {
+        return Err(format!("Expected {} components, got {}", rows * cols, values.len()));
+    }
+    Ok(values)
+}
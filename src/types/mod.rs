@@ -1,15 +1,29 @@
+use std::sync::Arc;
 use crate::error::AamlError;
 use crate::types::primitive_type::PrimitiveType;
 
+pub(crate) mod color;
+pub(crate) mod data;
+pub(crate) mod math;
 pub(crate) mod physics;
 pub(crate) mod primitive_type;
-mod math;
-mod time;
+pub(crate) mod time;
+pub(crate) mod units;
 
-pub trait Type {
+/// A registered type, shared between the config and the `@type` aliases that point at it.
+pub(crate) type SharedType = Arc<dyn Type>;
+
+pub trait Type: Send + Sync {
     fn from_name(name: &str) -> Result<Self, AamlError> where Self: Sized;
     fn base_type(&self) -> PrimitiveType;
     fn validate(&self, value: &str) -> Result<(), AamlError>;
+
+    /// The value as a number in this type's unit. Types with units (`physics::meter`)
+    /// convert compatible values such as `3 ft`; the default parses a plain number.
+    fn quantity(&self, value: &str) -> Result<f64, AamlError> {
+        self.validate(value)?;
+        value.trim().parse().map_err(|_| AamlError::InvalidValue(format!("Expected a number, got '{}'", value)))
+    }
 }
 
 pub fn resolve_builtin(path: &str) -> Result<Box<dyn Type>, AamlError> {
@@ -18,6 +32,7 @@ pub fn resolve_builtin(path: &str) -> Result<Box<dyn Type>, AamlError> {
     match parts.as_slice() {
         ["math", name] => Ok(Box::new(math::MathTypes::from_name(name)?)),
         ["time", name] => Ok(Box::new(time::TimeTypes::from_name(name)?)),
+        ["data", name] => Ok(Box::new(data::DataTypes::from_name(name)?)),
         ["physics", name] => Ok(Box::new(physics::PhysicsTypes::from_name(name)?)),
         [name] => Ok(Box::new(primitive_type::PrimitiveType::from_name(name)?)),
         _ => Err(AamlError::NotFound(path.to_string())),
@@ -2,6 +2,7 @@ use std::fmt;
 use crate::error::AamlError;
 use crate::types::primitive_type::PrimitiveType;
 use crate::types::Type;
+use crate::types::units::{self, Unit};
 
 pub(crate) enum PhysicsTypes {
     Meter,
@@ -307,19 +308,179 @@ impl Type for PhysicsTypes {
     }
 
     fn validate(&self, value: &str) -> Result<(), AamlError> {
-        match self.base_type() {
-            PrimitiveType::I32 => {
-                value.parse::<i32>().map_err(|_| AamlError::InvalidValue(format!("Expected integer for unit {self}, got '{value}'")))?;
-            }
-            PrimitiveType::F64 => {
-                value.parse::<f64>().map_err(|_| AamlError::InvalidValue(format!("Expected number for unit {}, got '{}'", self, value)))?;
+        self.quantity(value).map(|_| ())
+    }
+
+    /// A bare number is taken in this unit; `30 km/h` or `3 ft` is converted into it when
+    /// the dimensions agree.
+    fn quantity(&self, value: &str) -> Result<f64, AamlError> {
+        let (number, unit) = units::split_quantity(value)
+            .map_err(|_| AamlError::InvalidValue(format!("Expected number for unit {}, got '{}'", self, value)))?;
+        if unit.is_empty() {
+            if self.base_type() == PrimitiveType::I32 && value.trim().parse::<i32>().is_err() {
+                return Err(AamlError::InvalidValue(format!("Expected integer for unit {self}, got '{value}'")));
             }
-            _ => return Err(AamlError::InvalidValue(format!("Unsupported base type for unit {}", self))),
+            return Ok(number);
+        }
+
+        let invalid = |e: String| AamlError::InvalidValue(format!("Invalid {} '{}': {}", self, value, e));
+        let from = units::parse_unit(unit).map_err(invalid)?;
+        let to = self.unit();
+        if from.dimension != to.dimension {
+            return Err(invalid(format!("{} ({}) is not compatible with {} ({})", unit, from, self, to)));
         }
-        Ok(())
+        Ok(from.convert(number, &to))
     }
 }
 
+impl PhysicsTypes {
+    /// The unit as an expression understood by [`units::parse_unit`].
+    fn symbol(&self) -> &'static str {
+        match self {
+            PhysicsTypes::Meter => "m",
+            PhysicsTypes::Kilogram => "kg",
+            PhysicsTypes::Second => "s",
+            PhysicsTypes::Ampere => "A",
+            PhysicsTypes::Kelvin => "K",
+            PhysicsTypes::Mole => "mol",
+            PhysicsTypes::Candela => "cd",
+            PhysicsTypes::SquareMeter => "m^2",
+            PhysicsTypes::CubicMeter => "m^3",
+            PhysicsTypes::MeterPerSecond => "m/s",
+            PhysicsTypes::MeterPerSecondSquared => "m/s^2",
+            PhysicsTypes::RadianPerSecond => "rad/s",
+            PhysicsTypes::RadianPerSecondSquared => "rad/s^2",
+            PhysicsTypes::Hertz => "Hz",
+            PhysicsTypes::KilogramPerCubicMeter => "kg/m^3",
+            PhysicsTypes::KilogramMeterPerSecond => "kg*m/s",
+            PhysicsTypes::Newton => "N",
+            PhysicsTypes::NewtonMeter => "N*m",
+            PhysicsTypes::Pascal => "Pa",
+            PhysicsTypes::Joule => "J",
+            PhysicsTypes::Watt => "W",
+            PhysicsTypes::NewtonPerMeter => "N/m",
+            PhysicsTypes::Dimensionless => "1",
+            PhysicsTypes::KilogramSquareMeter => "kg*m^2",
+            PhysicsTypes::JoulePerKilogramKelvin => "J/(kg*K)",
+            PhysicsTypes::JoulePerKilogram => "J/kg",
+            PhysicsTypes::JoulePerKelvin => "J/K",
+            PhysicsTypes::Coulomb => "C",
+            PhysicsTypes::Volt => "V",
+            PhysicsTypes::Ohm => "Ω",
+            PhysicsTypes::OhmMeter => "Ω*m",
+            PhysicsTypes::Farad => "F",
+            PhysicsTypes::VoltPerMeter => "V/m",
+            PhysicsTypes::Tesla => "T",
+            PhysicsTypes::Weber => "Wb",
+            PhysicsTypes::Henry => "H",
+            PhysicsTypes::Dioptre => "1/m",
+            PhysicsTypes::Percentage => "%",
+            PhysicsTypes::Becquerel => "Bq",
+            PhysicsTypes::Gray => "Gy",
+            PhysicsTypes::Sievert => "Sv",
+            PhysicsTypes::ElectronVolt => "eV",
+            PhysicsTypes::Barn => "barn",
+            PhysicsTypes::LightYear => "ly",
+            PhysicsTypes::Parsec => "pc",
+            PhysicsTypes::AstronomicalUnit => "au",
+            PhysicsTypes::HubbleConstant => "km/s/Mpc",
+            PhysicsTypes::Siemens => "S",
+            PhysicsTypes::CoulombPerCubicMeter => "C/m^3",
+            PhysicsTypes::CoulombPerSquareMeter => "C/m^2",
+            PhysicsTypes::FaradPerMeter => "F/m",
+            PhysicsTypes::HenryPerMeter => "H/m",
+            PhysicsTypes::AmperePerMeter => "A/m",
+            PhysicsTypes::AmperePerSquareMeter => "A/m^2",
+            PhysicsTypes::VoltPerKelvin => "V/K",
+            PhysicsTypes::PascalSecond => "Pa*s",
+            PhysicsTypes::SquareMeterPerSecond => "m^2/s",
+            PhysicsTypes::NewtonSecond => "N*s",
+            PhysicsTypes::NewtonPerCubicMeter => "N/m^3",
+            PhysicsTypes::JouleSecond => "J*s",
+            PhysicsTypes::KilogramPerMole => "kg/mol",
+            PhysicsTypes::CubicMeterPerKilogram => "m^3/kg",
+            PhysicsTypes::MeterPerCubicSecond => "m/s^3",
+            PhysicsTypes::Lumen => "lm",
+            PhysicsTypes::Lux => "lx",
+            PhysicsTypes::LumenSecond => "lm*s",
+            PhysicsTypes::CandelaPerSquareMeter => "cd/m^2",
+            PhysicsTypes::WattPerSteradian => "W/sr",
+            PhysicsTypes::WattPerSquareMeter => "W/m^2",
+            PhysicsTypes::WattPerMeterKelvin => "W/(m*K)",
+            PhysicsTypes::JoulePerSquareMeter => "J/m^2",
+            PhysicsTypes::Radian => "rad",
+            PhysicsTypes::Steradian => "sr",
+            PhysicsTypes::Bit => "bit",
+            PhysicsTypes::Decibel => "dB",
+            PhysicsTypes::Katal => "kat",
+            PhysicsTypes::MolePerCubicMeter => "mol/m^3",
+            PhysicsTypes::NewtonPerMeterSquared => "N/m^2",
+            PhysicsTypes::JoulePerMole => "J/mol",
+            PhysicsTypes::JoulePerMoleKelvin => "J/(mol*K)",
+            PhysicsTypes::KelvinPerWatt => "K/W",
+            PhysicsTypes::KilogramPerSecond => "kg/s",
+            PhysicsTypes::CubicMeterPerSecond => "m^3/s",
+            PhysicsTypes::InverseMeter => "1/m",
+            PhysicsTypes::NewtonPerCoulomb => "N/C",
+            PhysicsTypes::WeberPerMeter => "Wb/m",
+            PhysicsTypes::TeslaSquareMeter => "T*m^2",
+            PhysicsTypes::ArcDegree => "deg",
+            PhysicsTypes::ArcMinute => "arcmin",
+            PhysicsTypes::ArcSecond => "arcsec",
+            PhysicsTypes::Bar => "bar",
+            PhysicsTypes::MillimeterOfMercury => "mmHg",
+            PhysicsTypes::Atmosphere => "atm",
+            PhysicsTypes::Torr => "Torr",
+            PhysicsTypes::Poise => "P",
+            PhysicsTypes::Stokes => "St",
+            PhysicsTypes::Sverdrup => "hm^3/s",
+            PhysicsTypes::Rayl => "Pa*s/m",
+            PhysicsTypes::Gal => "Gal",
+            PhysicsTypes::Maxwell => "Mx",
+            PhysicsTypes::Gauss => "G",
+            PhysicsTypes::Oersted => "Oe",
+            PhysicsTypes::Gilbert => "Oe*cm",
+            PhysicsTypes::Franklin => "Fr",
+            PhysicsTypes::Debye => "D",
+            PhysicsTypes::Angstrom => "Å",
+            PhysicsTypes::Lambert => "lambert",
+            PhysicsTypes::Phot => "ph",
+            PhysicsTypes::Stilb => "sb",
+            PhysicsTypes::Kayser => "1/cm",
+            PhysicsTypes::Calorie => "cal",
+            PhysicsTypes::BritishThermalUnit => "Btu",
+            PhysicsTypes::Langley => "langley",
+            PhysicsTypes::Fahrenheit => "°F",
+            PhysicsTypes::Celsius => "°C",
+            PhysicsTypes::Rankine => "°R",
+            PhysicsTypes::Curie => "Ci",
+            PhysicsTypes::Roentgen => "R",
+            PhysicsTypes::Rutherford => "Rd",
+            PhysicsTypes::Fermi => "fm",
+            PhysicsTypes::Dalton => "Da",
+            PhysicsTypes::Byte => "B",
+            PhysicsTypes::Baud => "Bd",
+            PhysicsTypes::Erlang => "1",
+            PhysicsTypes::MetabolicEquivalent => "MET",
+            PhysicsTypes::Jansky => "Jy",
+            PhysicsTypes::MachNumber => "1",
+            PhysicsTypes::Knots => "kn",
+            PhysicsTypes::NauticalMile => "nmi",
+            PhysicsTypes::Horsepower => "hp",
+        }
+    }
+
+    pub(crate) fn unit(&self) -> Unit {
+        units::parse_unit(self.symbol()).expect("built-in unit symbols parse")
+    }
+}
+
+/// The unit of the `physics::` type called `name`, so type names double as unit names
+/// (`5 nauticalMile`).
+pub(crate) fn unit_of_name(name: &str) -> Option<Unit> {
+    PhysicsTypes::from_name(name).ok().map(|t| t.unit())
+}
+
 impl fmt::Display for PhysicsTypes {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         let s = match self {
@@ -1,14 +1,59 @@
 use std::fmt;
+use std::net::{Ipv4Addr, Ipv6Addr, SocketAddr};
 use crate::error::AamlError;
 use crate::types::Type;
+use crate::types::color;
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum PrimitiveType {
+    I8,
+    I16,
     I32,
+    I64,
+    I128,
+    U8,
+    U16,
+    U32,
+    U64,
+    U128,
+    F32,
     F64,
     String,
     Bool,
+    Char,
     Color,
+    /// `xxxxxxxx-xxxx-xxxx-xxxx-xxxxxxxxxxxx` in hex digits.
+    Uuid,
+    Email,
+    /// `scheme://` followed by a non-empty remainder without whitespace.
+    Url,
+    Ipv4,
+    Ipv6,
+    /// `host:port` with an IPv4 address or a bracketed IPv6 address.
+    SocketAddr,
+    /// Any non-empty path; existence is not checked.
+    Path,
+    /// Semantic version `MAJOR.MINOR.PATCH[-pre][+build]`.
+    Semver,
+    /// Standard alphabet with `=` padding.
+    Base64,
+    /// Hex digits with an optional `0x` prefix.
+    Hex,
+}
+
+impl PrimitiveType {
+    pub fn is_integer(&self) -> bool {
+        use PrimitiveType::*;
+        matches!(self, I8 | I16 | I32 | I64 | I128 | U8 | U16 | U32 | U64 | U128)
+    }
+
+    pub fn is_float(&self) -> bool {
+        matches!(self, PrimitiveType::F32 | PrimitiveType::F64)
+    }
+
+    pub fn is_numeric(&self) -> bool {
+        self.is_integer() || self.is_float()
+    }
 }
 
 impl Type for PrimitiveType {
@@ -17,11 +62,32 @@ impl Type for PrimitiveType {
         Self: Sized
     {
         match name {
+            "i8" => Ok(PrimitiveType::I8),
+            "i16" => Ok(PrimitiveType::I16),
             "i32" => Ok(PrimitiveType::I32),
+            "i64" => Ok(PrimitiveType::I64),
+            "i128" => Ok(PrimitiveType::I128),
+            "u8" => Ok(PrimitiveType::U8),
+            "u16" => Ok(PrimitiveType::U16),
+            "u32" => Ok(PrimitiveType::U32),
+            "u64" => Ok(PrimitiveType::U64),
+            "u128" => Ok(PrimitiveType::U128),
+            "f32" => Ok(PrimitiveType::F32),
             "f64" => Ok(PrimitiveType::F64),
             "string" => Ok(PrimitiveType::String),
             "bool" => Ok(PrimitiveType::Bool),
+            "char" => Ok(PrimitiveType::Char),
             "color" => Ok(PrimitiveType::Color),
+            "uuid" => Ok(PrimitiveType::Uuid),
+            "email" => Ok(PrimitiveType::Email),
+            "url" => Ok(PrimitiveType::Url),
+            "ipv4" => Ok(PrimitiveType::Ipv4),
+            "ipv6" => Ok(PrimitiveType::Ipv6),
+            "socket_addr" => Ok(PrimitiveType::SocketAddr),
+            "path" => Ok(PrimitiveType::Path),
+            "semver" => Ok(PrimitiveType::Semver),
+            "base64" => Ok(PrimitiveType::Base64),
+            "hex" => Ok(PrimitiveType::Hex),
             _ => Err(crate::error::AamlError::NotFound(name.to_string())),
         }
     }
@@ -31,16 +97,44 @@ impl Type for PrimitiveType {
     }
 
     fn validate(&self, value: &str) -> Result<(), AamlError> {
+        let expected = |ok: bool| {
+            if ok {
+                Ok(())
+            } else {
+                Err(AamlError::InvalidValue(format!("Expected {}, got '{}'", self, value)))
+            }
+        };
+
         match self {
-            PrimitiveType::I32 => {
-                value.parse::<i32>().map_err(|_| {
-                    AamlError::InvalidValue(format!("Expected i32, got '{}'", value))
-                })?;
+            PrimitiveType::I8 => expected(value.parse::<i8>().is_ok())?,
+            PrimitiveType::I16 => expected(value.parse::<i16>().is_ok())?,
+            PrimitiveType::I32 => expected(value.parse::<i32>().is_ok())?,
+            PrimitiveType::I64 => expected(value.parse::<i64>().is_ok())?,
+            PrimitiveType::I128 => expected(value.parse::<i128>().is_ok())?,
+            PrimitiveType::U8 => expected(value.parse::<u8>().is_ok())?,
+            PrimitiveType::U16 => expected(value.parse::<u16>().is_ok())?,
+            PrimitiveType::U32 => expected(value.parse::<u32>().is_ok())?,
+            PrimitiveType::U64 => expected(value.parse::<u64>().is_ok())?,
+            PrimitiveType::U128 => expected(value.parse::<u128>().is_ok())?,
+            PrimitiveType::F32 => {
+                // `1e40` parses as infinity; only accept infinity when it was written out.
+                let parsed = value.parse::<f32>();
+                expected(parsed.is_ok_and(|f| f.is_finite() || value.parse::<f64>().is_ok_and(f64::is_infinite)))?
             }
-            PrimitiveType::F64 => {
-                value.parse::<f64>().map_err(|_| {
-                    AamlError::InvalidValue(format!("Expected f64, got '{}'", value))
-                })?;
+            PrimitiveType::F64 => expected(value.parse::<f64>().is_ok())?,
+            PrimitiveType::Char => expected(value.chars().count() == 1)?,
+            PrimitiveType::Uuid => expected(is_uuid(value))?,
+            PrimitiveType::Email => expected(is_email(value))?,
+            PrimitiveType::Url => expected(is_url(value))?,
+            PrimitiveType::Ipv4 => expected(value.parse::<Ipv4Addr>().is_ok())?,
+            PrimitiveType::Ipv6 => expected(value.parse::<Ipv6Addr>().is_ok())?,
+            PrimitiveType::SocketAddr => expected(value.parse::<SocketAddr>().is_ok())?,
+            PrimitiveType::Path => expected(!value.is_empty() && !value.contains('\0'))?,
+            PrimitiveType::Semver => expected(is_semver(value))?,
+            PrimitiveType::Base64 => expected(is_base64(value))?,
+            PrimitiveType::Hex => {
+                let digits = value.strip_prefix("0x").or_else(|| value.strip_prefix("0X")).unwrap_or(value);
+                expected(!digits.is_empty() && digits.chars().all(|c| c.is_ascii_hexdigit()))?
             }
             PrimitiveType::String => {
                 // Любая строка валидна
@@ -54,17 +148,8 @@ impl Type for PrimitiveType {
                 }
             }
             PrimitiveType::Color => {
-                // Waiting hex #RRGGBB or #RRGGBBAA
-                if !value.starts_with('#') || (value.len() != 7 && value.len() != 9) {
-                    return Err(AamlError::InvalidValue(format!(
-                        "Expected color in #RRGGBB or #RRGGBBAA format, got '{}'", value
-                    )));
-                }
-                if u64::from_str_radix(&value[1..], 16).is_err() {
-                    return Err(AamlError::InvalidValue(format!(
-                        "Invalid hex color '{}'", value
-                    )));
-                }
+                color::parse_color(value)
+                    .map_err(|e| AamlError::InvalidValue(format!("Invalid color '{}': {}", value, e)))?;
             }
         }
         Ok(())
@@ -74,12 +159,101 @@ impl Type for PrimitiveType {
 impl fmt::Display for PrimitiveType {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         let s = match self {
+            PrimitiveType::I8 => "i8",
+            PrimitiveType::I16 => "i16",
             PrimitiveType::I32 => "i32",
+            PrimitiveType::I64 => "i64",
+            PrimitiveType::I128 => "i128",
+            PrimitiveType::U8 => "u8",
+            PrimitiveType::U16 => "u16",
+            PrimitiveType::U32 => "u32",
+            PrimitiveType::U64 => "u64",
+            PrimitiveType::U128 => "u128",
+            PrimitiveType::F32 => "f32",
             PrimitiveType::F64 => "f64",
             PrimitiveType::String => "string",
             PrimitiveType::Bool => "bool",
+            PrimitiveType::Char => "char",
             PrimitiveType::Color => "color",
+            PrimitiveType::Uuid => "uuid",
+            PrimitiveType::Email => "email",
+            PrimitiveType::Url => "url",
+            PrimitiveType::Ipv4 => "ipv4",
+            PrimitiveType::Ipv6 => "ipv6",
+            PrimitiveType::SocketAddr => "socket_addr",
+            PrimitiveType::Path => "path",
+            PrimitiveType::Semver => "semver",
+            PrimitiveType::Base64 => "base64",
+            PrimitiveType::Hex => "hex",
         };
         write!(f, "{}", s)
     }
-}
\ No newline at end of file
+}
+
+fn is_uuid(value: &str) -> bool {
+    let groups: Vec<&str> = value.split('-').collect();
+    groups.len() == 5
+        && groups.iter().zip([8, 4, 4, 4, 12]).all(|(g, len)| {
+            g.len() == len && g.chars().all(|c| c.is_ascii_hexdigit())
+        })
+}
+
+/// `local@domain.tld`: a pragmatic subset of RFC 5322 without quoted local parts.
+fn is_email(value: &str) -> bool {
+    let Some((local, domain)) = value.rsplit_once('@') else { return false };
+    let local_ok = !local.is_empty()
+        && !local.starts_with('.')
+        && !local.ends_with('.')
+        && !local.contains("..")
+        && local.chars().all(|c| c.is_ascii_alphanumeric() || "!#$%&'*+/=?^_`{|}~.-".contains(c));
+    local_ok && domain.contains('.') && is_hostname(domain)
+}
+
+fn is_hostname(host: &str) -> bool {
+    host.len() <= 253
+        && host.split('.').all(|label| {
+            !label.is_empty()
+                && label.len() <= 63
+                && !label.starts_with('-')
+                && !label.ends_with('-')
+                && label.chars().all(|c| c.is_ascii_alphanumeric() || c == '-')
+        })
+}
+
+fn is_url(value: &str) -> bool {
+    let Some((scheme, rest)) = value.split_once("://") else { return false };
+    let scheme_ok = scheme.starts_with(|c: char| c.is_ascii_alphabetic())
+        && scheme.chars().all(|c| c.is_ascii_alphanumeric() || matches!(c, '+' | '-' | '.'));
+    scheme_ok && !rest.is_empty() && !rest.starts_with('/') && !rest.contains(char::is_whitespace)
+}
+
+fn is_semver(value: &str) -> bool {
+    let (version, build) = match value.split_once('+') {
+        Some((version, build)) => (version, Some(build)),
+        None => (value, None),
+    };
+    let (core, pre) = match version.split_once('-') {
+        Some((core, pre)) => (core, Some(pre)),
+        None => (version, None),
+    };
+
+    let numeric = |id: &str| !id.is_empty() && id.chars().all(|c| c.is_ascii_digit()) && (id == "0" || !id.starts_with('0'));
+    let ident = |id: &str| !id.is_empty() && id.chars().all(|c| c.is_ascii_alphanumeric() || c == '-');
+
+    let parts: Vec<&str> = core.split('.').collect();
+    parts.len() == 3
+        && parts.iter().all(|p| numeric(p))
+        && pre.is_none_or(|pre| {
+            pre.split('.').all(|id| ident(id) && (!id.chars().all(|c| c.is_ascii_digit()) || numeric(id)))
+        })
+        && build.is_none_or(|build| build.split('.').all(ident))
+}
+
+fn is_base64(value: &str) -> bool {
+    let body = value.trim_end_matches('=');
+    let padding = value.len() - body.len();
+    !value.is_empty()
+        && value.len().is_multiple_of(4)
+        && padding <= 2
+        && body.chars().all(|c| c.is_ascii_alphanumeric() || c == '+' || c == '/')
+}
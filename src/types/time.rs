@@ -1,9 +1,13 @@
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
 use crate::error::AamlError;
 use crate::types::primitive_type::PrimitiveType;
 use crate::types::Type;
 
 pub enum TimeTypes {
     DateTime,
+    Date,
+    Time,
+    TimezoneOffset,
     Duration,
     Year,
     Day,
@@ -19,6 +23,9 @@ impl Type for TimeTypes {
     {
         match name {
             "datetime" => Ok(TimeTypes::DateTime),
+            "date" => Ok(TimeTypes::Date),
+            "time" => Ok(TimeTypes::Time),
+            "timezone_offset" => Ok(TimeTypes::TimezoneOffset),
             "duration" => Ok(TimeTypes::Duration),
             "year" => Ok(TimeTypes::Year),
             "day" => Ok(TimeTypes::Day),
@@ -29,50 +36,35 @@ impl Type for TimeTypes {
     }
 
     fn base_type(&self) -> PrimitiveType {
-        PrimitiveType::F64
+        match self {
+            TimeTypes::Year | TimeTypes::Day | TimeTypes::Hour | TimeTypes::Minute => PrimitiveType::F64,
+            _ => PrimitiveType::String,
+        }
     }
 
     fn validate(&self, value: &str) -> Result<(), AamlError> {
         match self {
-            TimeTypes::DateTime => {
-                // Waiting format ISO 8601: YYYY-MM-DDTHH:MM:SS или YYYY-MM-DD
-                if value.len() < 10 {
-                    return Err(AamlError::InvalidValue(format!(
-                        "Invalid DateTime '{}': expected ISO 8601 format (YYYY-MM-DD or YYYY-MM-DDTHH:MM:SS)",
-                        value
-                    )));
-                }
-                let date_part = &value[..10];
-                let parts: Vec<&str> = date_part.split('-').collect();
-                if parts.len() != 3
-                    || parts[0].len() != 4
-                    || parts[1].len() != 2
-                    || parts[2].len() != 2
-                    || parts[0].parse::<u32>().is_err()
-                    || parts[1].parse::<u32>().is_err()
-                    || parts[2].parse::<u32>().is_err()
-                {
-                    return Err(AamlError::InvalidValue(format!(
-                        "Invalid DateTime '{}': expected ISO 8601 format",
-                        value
-                    )));
-                }
-                Ok(())
-            }
-            TimeTypes::Duration => {
-                //  ISO 8601 duration (PnYnMnDTnHnMnS)
-                if value.starts_with('P') {
-                    Ok(()) 
-                } else {
-                    value.parse::<f64>().map_err(|_| {
-                        AamlError::InvalidValue(format!(
-                            "Invalid Duration '{}': expected number (seconds) or ISO 8601 duration",
-                            value
-                        ))
-                    })?;
-                    Ok(())
-                }
-            }
+            TimeTypes::DateTime => parse_datetime(value).map(|_| ()).map_err(|details| {
+                AamlError::InvalidValue(format!(
+                    "Invalid DateTime '{}': {} (expected ISO 8601, e.g. 2024-01-15 or 2024-01-15T10:30:00Z)",
+                    value, details
+                ))
+            }),
+            TimeTypes::Date => parse_date(value).map(|_| ()).map_err(|details| {
+                AamlError::InvalidValue(format!("Invalid date '{}': {} (expected YYYY-MM-DD)", value, details))
+            }),
+            TimeTypes::Time => parse_time_with_offset(value).map(|_| ()).map_err(|details| {
+                AamlError::InvalidValue(format!("Invalid time '{}': {} (expected HH:MM[:SS[.fff]])", value, details))
+            }),
+            TimeTypes::TimezoneOffset => parse_offset(value).map(|_| ()).map_err(|details| {
+                AamlError::InvalidValue(format!("Invalid timezone offset '{}': {} (expected Z or ±HH:MM)", value, details))
+            }),
+            TimeTypes::Duration => parse_duration(value).map(|_| ()).map_err(|details| {
+                AamlError::InvalidValue(format!(
+                    "Invalid Duration '{}': {} (expected seconds, an ISO 8601 duration like PT1H30M or 1h30m)",
+                    value, details
+                ))
+            }),
             TimeTypes::Year | TimeTypes::Day | TimeTypes::Hour | TimeTypes::Minute => {
                 value.parse::<f64>().map_err(|_| {
                     AamlError::InvalidValue(format!(
@@ -84,4 +76,291 @@ impl Type for TimeTypes {
             }
         }
     }
-}
\ No newline at end of file
+}
+
+/// A calendar date, checked against the length of its month.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct Date {
+    pub year: i64,
+    pub month: u32,
+    pub day: u32,
+}
+
+/// Time of day with nanosecond precision.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct TimeOfDay {
+    pub hour: u32,
+    pub minute: u32,
+    pub second: u32,
+    pub nanos: u32,
+}
+
+fn fixed_digits(s: &str, len: usize, what: &str) -> Result<u32, String> {
+    if s.len() != len || !s.bytes().all(|b| b.is_ascii_digit()) {
+        return Err(format!("{} must be {} digits", what, len));
+    }
+    s.parse().map_err(|_| format!("bad {}", what))
+}
+
+fn is_leap_year(year: i64) -> bool {
+    year % 4 == 0 && (year % 100 != 0 || year % 400 == 0)
+}
+
+fn days_in_month(year: i64, month: u32) -> u32 {
+    match month {
+        2 if is_leap_year(year) => 29,
+        2 => 28,
+        4 | 6 | 9 | 11 => 30,
+        _ => 31,
+    }
+}
+
+/// `YYYY-MM-DD`.
+pub(crate) fn parse_date(value: &str) -> Result<Date, String> {
+    let mut parts = value.split('-');
+    let (Some(year), Some(month), Some(day), None) = (parts.next(), parts.next(), parts.next(), parts.next()) else {
+        return Err("expected YYYY-MM-DD".into());
+    };
+    let year = fixed_digits(year, 4, "year")? as i64;
+    let month = fixed_digits(month, 2, "month")?;
+    let day = fixed_digits(day, 2, "day")?;
+
+    if !(1..=12).contains(&month) {
+        return Err(format!("month {} is out of range", month));
+    }
+    if day == 0 || day > days_in_month(year, month) {
+        return Err(format!("day {} does not exist in {:04}-{:02}", day, year, month));
+    }
+    Ok(Date { year, month, day })
+}
+
+/// `HH:MM`, `HH:MM:SS` or `HH:MM:SS.fffffffff`.
+pub(crate) fn parse_time(value: &str) -> Result<TimeOfDay, String> {
+    let (clock, fraction) = match value.split_once(['.', ',']) {
+        Some((clock, fraction)) => (clock, Some(fraction)),
+        None => (value, None),
+    };
+    let parts: Vec<&str> = clock.split(':').collect();
+    let (hour, minute, second) = match parts[..] {
+        [h, m] if fraction.is_none() => (h, m, "00"),
+        [h, m, s] => (h, m, s),
+        _ => return Err("expected HH:MM or HH:MM:SS".into()),
+    };
+    let hour = fixed_digits(hour, 2, "hour")?;
+    let minute = fixed_digits(minute, 2, "minute")?;
+    let second = fixed_digits(second, 2, "second")?;
+    if hour > 23 || minute > 59 || second > 59 {
+        return Err("time is out of range".into());
+    }
+
+    let nanos = match fraction {
+        Some(f) if !f.is_empty() && f.len() <= 9 && f.bytes().all(|b| b.is_ascii_digit()) => {
+            format!("{:0<9}", f).parse().map_err(|_| "bad fraction".to_string())?
+        }
+        Some(_) => return Err("fraction of a second must be 1 to 9 digits".into()),
+        None => 0,
+    };
+    Ok(TimeOfDay { hour, minute, second, nanos })
+}
+
+/// `Z`, `±HH:MM`, `±HHMM` or `±HH`, as seconds east of UTC.
+pub(crate) fn parse_offset(value: &str) -> Result<i32, String> {
+    if value == "Z" || value == "z" {
+        return Ok(0);
+    }
+    let (sign, rest) = match value.as_bytes().first() {
+        Some(b'+') => (1, &value[1..]),
+        Some(b'-') => (-1, &value[1..]),
+        _ => return Err("expected Z or a sign".into()),
+    };
+    let (hours, minutes) = match rest.len() {
+        2 => (rest, "00"),
+        4 if rest.is_char_boundary(2) => rest.split_at(2),
+        5 if rest.as_bytes()[2] == b':' => (&rest[..2], &rest[3..]),
+        _ => return Err("expected ±HH:MM".into()),
+    };
+    let hours = fixed_digits(hours, 2, "offset hours")?;
+    let minutes = fixed_digits(minutes, 2, "offset minutes")?;
+    if hours > 23 || minutes > 59 {
+        return Err("offset is out of range".into());
+    }
+    Ok(sign * (hours * 3600 + minutes * 60) as i32)
+}
+
+/// A time of day and its offset in seconds east of UTC, if one was given.
+pub(crate) type ZonedTime = (TimeOfDay, Option<i32>);
+
+/// A time of day with an optional trailing offset (`10:30:00+02:00`).
+fn parse_time_with_offset(value: &str) -> Result<ZonedTime, String> {
+    match value.find(['Z', 'z', '+', '-']) {
+        Some(idx) => Ok((parse_time(&value[..idx])?, Some(parse_offset(&value[idx..])?))),
+        None => Ok((parse_time(value)?, None)),
+    }
+}
+
+/// `YYYY-MM-DD` or `YYYY-MM-DDTHH:MM[:SS[.fff]][offset]`; `t` or a space may replace `T`.
+pub(crate) fn parse_datetime(value: &str) -> Result<(Date, Option<ZonedTime>), String> {
+    match value.find(['T', 't', ' ']) {
+        Some(idx) => Ok((parse_date(&value[..idx])?, Some(parse_time_with_offset(&value[idx + 1..])?))),
+        None => Ok((parse_date(value)?, None)),
+    }
+}
+
+/// Days between 1970-01-01 and `date` in the proleptic Gregorian calendar.
+fn days_from_civil(date: Date) -> i64 {
+    let year = if date.month <= 2 { date.year - 1 } else { date.year };
+    let era = if year >= 0 { year } else { year - 399 } / 400;
+    let year_of_era = year - era * 400;
+    let month = i64::from(date.month);
+    let day_of_year = (153 * (if month > 2 { month - 3 } else { month + 9 }) + 2) / 5 + i64::from(date.day) - 1;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+    era * 146097 + day_of_era - 719468
+}
+
+/// The instant a datetime denotes. A value without an offset (or without a time) is read
+/// as UTC.
+pub(crate) fn datetime_to_system_time(value: &str) -> Result<SystemTime, String> {
+    let (date, time) = parse_datetime(value)?;
+    let (time, offset) = time.unwrap_or((TimeOfDay { hour: 0, minute: 0, second: 0, nanos: 0 }, None));
+
+    let seconds = days_from_civil(date) * 86_400
+        + i64::from(time.hour * 3600 + time.minute * 60 + time.second)
+        - i64::from(offset.unwrap_or(0));
+    // The representable range is platform-specific: Windows stops before 1601.
+    let instant = if seconds >= 0 {
+        UNIX_EPOCH.checked_add(Duration::from_secs(seconds as u64))
+    } else {
+        UNIX_EPOCH.checked_sub(Duration::from_secs(seconds.unsigned_abs()))
+    };
+    instant
+        .and_then(|instant| instant.checked_add(Duration::from_nanos(u64::from(time.nanos))))
+        .ok_or_else(|| "out of range for this platform's clock".to_string())
+}
+
+/// A parsed duration. `calendar` is set when it contains years or months, which are
+/// counted as 365 and 30 days.
+pub(crate) struct ParsedDuration {
+    pub duration: Duration,
+    pub calendar: bool,
+}
+
+/// Seconds (`90`, `1.5`), an ISO 8601 duration (`P1DT2H`, `PT0.5S`, `P2W`) or a human
+/// duration (`1h30m`, `250ms`, `2d 12h`, `30 s`).
+pub(crate) fn parse_duration(value: &str) -> Result<ParsedDuration, String> {
+    let value = value.trim();
+    if let Ok(seconds) = value.parse::<f64>() {
+        return Duration::try_from_secs_f64(seconds)
+            .map(|duration| ParsedDuration { duration, calendar: false })
+            .map_err(|_| "seconds must be a finite, non-negative number".into());
+    }
+    match value.strip_prefix('P') {
+        Some(iso) => parse_iso_duration(iso),
+        None => parse_human_duration(value).map(|duration| ParsedDuration { duration, calendar: false }),
+    }
+}
+
+/// Splits `1.5h30m` into `(1.5, "h")`, `(30, "m")`; whitespace is allowed between parts
+/// and between a number and its unit (`1 h 30 m`).
+fn duration_parts(value: &str) -> Result<Vec<(&str, &str)>, String> {
+    let mut parts = Vec::new();
+    let mut rest = value.trim_start();
+    while !rest.is_empty() {
+        let number_end = rest.find(|c: char| !(c.is_ascii_digit() || c == '.')).unwrap_or(rest.len());
+        let (number, tail) = rest.split_at(number_end);
+        let tail = tail.trim_start();
+        let unit_end = tail.find(|c: char| c.is_ascii_digit() || c == '.' || c.is_whitespace()).unwrap_or(tail.len());
+        let (unit, tail) = tail.split_at(unit_end);
+        if number.is_empty() || unit.is_empty() {
+            return Err(format!("expected a number followed by a unit at '{}'", rest));
+        }
+        parts.push((number, unit));
+        rest = tail.trim_start();
+    }
+    if parts.is_empty() {
+        return Err("duration is empty".into());
+    }
+    Ok(parts)
+}
+
+fn duration_number(number: &str) -> Result<f64, String> {
+    if number.starts_with('.') || number.ends_with('.') || number.matches('.').count() > 1 {
+        return Err(format!("bad number '{}'", number));
+    }
+    number.parse().map_err(|_| format!("bad number '{}'", number))
+}
+
+fn parse_human_duration(value: &str) -> Result<Duration, String> {
+    let mut seconds = 0.0;
+    for (number, unit) in duration_parts(value)? {
+        let scale = match unit {
+            "ns" => 1e-9,
+            "us" | "µs" => 1e-6,
+            "ms" => 1e-3,
+            "s" => 1.0,
+            "m" | "min" => 60.0,
+            "h" => 3600.0,
+            "d" => 86_400.0,
+            "w" => 604_800.0,
+            _ => return Err(format!("unknown unit '{}'", unit)),
+        };
+        seconds += duration_number(number)? * scale;
+    }
+    Duration::try_from_secs_f64(seconds).map_err(|_| "duration is too large".into())
+}
+
+/// The part after `P`: `[nY][nM][nW][nD][T[nH][nM][nS]]`, designators in this order, a
+/// fraction only on the last one.
+fn parse_iso_duration(iso: &str) -> Result<ParsedDuration, String> {
+    let (date, time) = match iso.split_once('T') {
+        Some((_, "")) => return Err("'T' must be followed by a time component".into()),
+        Some((date, time)) => (date, Some(time)),
+        None => (iso, None),
+    };
+    if iso.contains(char::is_whitespace) {
+        return Err("unexpected whitespace".into());
+    }
+
+    let mut parts = Vec::new();
+    for (section, designators) in [(date, "YMWD"), (time.unwrap_or(""), "HMS")] {
+        let section_parts = if section.is_empty() { Vec::new() } else { duration_parts(section)? };
+        let mut next = 0;
+        for (number, unit) in section_parts {
+            let position = designators
+                .find(unit)
+                .filter(|_| unit.len() == 1)
+                .ok_or_else(|| format!("unexpected designator '{}'", unit))?;
+            if position < next {
+                return Err(format!("designator '{}' is repeated or out of order", unit));
+            }
+            next = position + 1;
+            parts.push((number, unit, designators == "HMS"));
+        }
+    }
+    if parts.is_empty() {
+        return Err("no components after 'P'".into());
+    }
+
+    let mut seconds = 0.0;
+    let mut calendar = false;
+    let last = parts.len() - 1;
+    for (i, (number, unit, in_time)) in parts.into_iter().enumerate() {
+        if number.contains('.') && i != last {
+            return Err("only the last component may have a fraction".into());
+        }
+        let scale = match (unit, in_time) {
+            ("Y", false) => 365.0 * 86_400.0,
+            ("M", false) => 30.0 * 86_400.0,
+            ("W", false) => 7.0 * 86_400.0,
+            ("D", false) => 86_400.0,
+            ("H", true) => 3600.0,
+            ("M", true) => 60.0,
+            _ => 1.0,
+        };
+        calendar |= !in_time && (unit == "Y" || unit == "M");
+        seconds += duration_number(number)? * scale;
+    }
+
+    Duration::try_from_secs_f64(seconds)
+        .map(|duration| ParsedDuration { duration, calendar })
+        .map_err(|_| "duration is too large".into())
+}
@@ -0,0 +1,336 @@
+//! Unit expressions (`km/h`, `kg*m/s^2`, `W/(m·K)`, `nauticalMile`) with SI prefixes and
+//! dimensional analysis, used by the `physics::` types.
+
+use std::f64::consts::PI;
+use std::fmt;
+
+/// Exponents of the base dimensions, in the order of [`DIMENSION_NAMES`]. Angles,
+/// information, decibels and METs are tracked as dimensions of their own so that `rad`,
+/// `bit`, `dB`, `MET` and plain numbers or percentages are not interchangeable; decibels
+/// are logarithmic and do not scale linearly into a ratio.
+pub(crate) type Dimension = [i8; 11];
+
+const DIMENSION_NAMES: [&str; 11] = ["m", "kg", "s", "A", "K", "mol", "cd", "rad", "bit", "dB", "MET"];
+const NONE: Dimension = [0; 11];
+
+/// A unit as a multiple of the SI base units: `si = value * scale + offset`. Only
+/// temperature scales have an offset.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) struct Unit {
+    pub dimension: Dimension,
+    pub scale: f64,
+    pub offset: f64,
+}
+
+impl Unit {
+    const ONE: Unit = Unit { dimension: NONE, scale: 1.0, offset: 0.0 };
+
+    fn base(index: usize) -> Unit {
+        let mut dimension = NONE;
+        dimension[index] = 1;
+        Unit { dimension, scale: 1.0, offset: 0.0 }
+    }
+
+    fn scaled(self, factor: f64) -> Unit {
+        Unit { scale: self.scale * factor, ..self }
+    }
+
+    fn times(self, other: Unit) -> Unit {
+        let mut dimension = self.dimension;
+        for (d, o) in dimension.iter_mut().zip(other.dimension) {
+            *d += o;
+        }
+        Unit { dimension, scale: self.scale * other.scale, offset: 0.0 }
+    }
+
+    fn powi(self, exponent: i32) -> Unit {
+        if exponent == 1 {
+            return self;
+        }
+        Unit {
+            dimension: self.dimension.map(|d| d * exponent as i8),
+            scale: self.scale.powi(exponent),
+            offset: 0.0,
+        }
+    }
+
+    /// Converts `value` expressed in `self` into `target`; the dimensions must match.
+    pub fn convert(&self, value: f64, target: &Unit) -> f64 {
+        (value * self.scale + self.offset - target.offset) / target.scale
+    }
+}
+
+impl fmt::Display for Unit {
+    /// The dimension in base units, e.g. `m·s^-1`, or `1` for dimensionless units.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let parts: Vec<String> = DIMENSION_NAMES
+            .iter()
+            .zip(self.dimension)
+            .filter(|(_, exp)| *exp != 0)
+            .map(|(name, exp)| if exp == 1 { name.to_string() } else { format!("{}^{}", name, exp) })
+            .collect();
+        if parts.is_empty() {
+            write!(f, "1")
+        } else {
+            write!(f, "{}", parts.join("·"))
+        }
+    }
+}
+
+const PREFIXES: [(&str, f64); 20] = [
+    ("Q", 1e30), ("R", 1e27), ("Y", 1e24), ("Z", 1e21), ("E", 1e18), ("P", 1e15),
+    ("T", 1e12), ("G", 1e9), ("M", 1e6), ("k", 1e3), ("h", 1e2), ("da", 1e1),
+    ("d", 1e-1), ("c", 1e-2), ("m", 1e-3), ("µ", 1e-6), ("u", 1e-6), ("n", 1e-9),
+    ("p", 1e-12), ("f", 1e-15),
+];
+
+const NAME_PREFIXES: [(&str, f64); 9] = [
+    ("giga", 1e9), ("mega", 1e6), ("kilo", 1e3), ("hecto", 1e2), ("deci", 1e-1),
+    ("centi", 1e-2), ("milli", 1e-3), ("micro", 1e-6), ("nano", 1e-9),
+];
+
+/// Unit symbols. The flag marks symbols that accept an SI prefix (`km`, `mA`, `GHz`).
+fn symbol(s: &str) -> Option<(Unit, bool)> {
+    let [m, kg, s_, a, k, mol, cd, rad, bit, db, met] = [0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10].map(Unit::base);
+    let newton = kg.times(m).times(s_.powi(-2));
+    let joule = newton.times(m);
+    let watt = joule.times(s_.powi(-1));
+    let pascal = newton.times(m.powi(-2));
+    let coulomb = a.times(s_);
+    let volt = watt.times(a.powi(-1));
+    let tesla = kg.times(s_.powi(-2)).times(a.powi(-1));
+    let weber = tesla.times(m.powi(2));
+    let sr = rad.powi(2);
+    let gram = kg.scaled(1e-3);
+    let celsius = Unit { offset: 273.15, ..k };
+
+    let unit = match s {
+        // SI base and derived units
+        "m" => (m, true),
+        "g" => (gram, true),
+        "s" => (s_, true),
+        "A" => (a, true),
+        "K" => (k, true),
+        "mol" => (mol, true),
+        "cd" => (cd, true),
+        "rad" => (rad, true),
+        "sr" => (sr, false),
+        "Hz" | "Bq" | "Bd" => (s_.powi(-1), true),
+        "N" => (newton, true),
+        "Pa" => (pascal, true),
+        "J" => (joule, true),
+        "W" => (watt, true),
+        "Wh" => (joule.scaled(3600.0), true),
+        "C" => (coulomb, true),
+        "V" => (volt, true),
+        "Ω" | "ohm" => (volt.times(a.powi(-1)), true),
+        "S" => (a.times(volt.powi(-1)), true),
+        "F" => (coulomb.times(volt.powi(-1)), true),
+        "T" => (tesla, true),
+        "Wb" => (weber, true),
+        "H" => (weber.times(a.powi(-1)), true),
+        "lm" => (cd.times(sr), true),
+        "lx" => (cd.times(sr).times(m.powi(-2)), true),
+        "Gy" | "Sv" => (joule.times(kg.powi(-1)), true),
+        "kat" => (mol.times(s_.powi(-1)), true),
+        "eV" => (joule.scaled(1.602_176_634e-19), true),
+        "Da" | "u" => (kg.scaled(1.660_539_066_60e-27), true),
+        "L" | "l" => (m.powi(3).scaled(1e-3), true),
+        "t" => (kg.scaled(1e3), true),
+        "bit" => (bit, true),
+        "B" => (bit.scaled(8.0), true),
+        "bps" => (bit.times(s_.powi(-1)), true),
+        "Jy" => (watt.times(m.powi(-2)).times(s_).scaled(1e-26), true),
+        "pc" => (m.scaled(3.085_677_581_491_367e16), true),
+        "bar" => (pascal.scaled(1e5), true),
+        "cal" => (joule.scaled(4.184), true),
+        "P" => (pascal.times(s_).scaled(0.1), true),
+        "St" => (m.powi(2).times(s_.powi(-1)).scaled(1e-4), true),
+
+        // Time
+        "min" => (s_.scaled(60.0), false),
+        "h" => (s_.scaled(3600.0), false),
+        "d" => (s_.scaled(86_400.0), false),
+        "wk" => (s_.scaled(604_800.0), false),
+
+        // Angles
+        "deg" | "°" => (rad.scaled(PI / 180.0), false),
+        "arcmin" | "′" => (rad.scaled(PI / 10_800.0), false),
+        "arcsec" | "″" => (rad.scaled(PI / 648_000.0), true),
+
+        // Temperature
+        "°C" | "degC" => (celsius, false),
+        "°F" | "degF" => (Unit { scale: 5.0 / 9.0, offset: 459.67 * 5.0 / 9.0, ..k }, false),
+        "°R" | "degR" => (k.scaled(5.0 / 9.0), false),
+
+        // Imperial, nautical and astronomical lengths, speeds and masses
+        "in" => (m.scaled(0.0254), false),
+        "ft" => (m.scaled(0.3048), false),
+        "yd" => (m.scaled(0.9144), false),
+        "mi" => (m.scaled(1609.344), false),
+        "nmi" => (m.scaled(1852.0), false),
+        "Å" => (m.scaled(1e-10), false),
+        "ly" => (m.scaled(9.460_730_472_580_8e15), false),
+        "au" | "AU" => (m.scaled(1.495_978_707e11), false),
+        "kn" => (m.times(s_.powi(-1)).scaled(1852.0 / 3600.0), false),
+        "mph" => (m.times(s_.powi(-1)).scaled(0.44704), false),
+        "kph" => (m.times(s_.powi(-1)).scaled(1.0 / 3.6), false),
+        "lb" => (kg.scaled(0.453_592_37), false),
+        "oz" => (kg.scaled(0.028_349_523_125), false),
+
+        // Other non-SI units
+        "atm" => (pascal.scaled(101_325.0), false),
+        "Torr" => (pascal.scaled(101_325.0 / 760.0), false),
+        "mmHg" => (pascal.scaled(133.322_387_415), false),
+        "psi" => (pascal.scaled(6_894.757_293_168), false),
+        "Gal" => (m.times(s_.powi(-2)).scaled(0.01), false),
+        "Mx" => (weber.scaled(1e-8), false),
+        "G" | "Gs" => (tesla.scaled(1e-4), false),
+        "Oe" => (a.times(m.powi(-1)).scaled(1e3 / (4.0 * PI)), false),
+        "Fr" | "statC" => (coulomb.scaled(3.335_640_951_981_52e-10), false),
+        "D" => (coulomb.times(m).scaled(3.335_640_951_981_52e-30), false),
+        "ph" => (cd.times(sr).times(m.powi(-2)).scaled(1e4), false),
+        "sb" => (cd.times(m.powi(-2)).scaled(1e4), false),
+        "barn" => (m.powi(2).scaled(1e-28), false),
+        "lambert" => (cd.times(m.powi(-2)).scaled(1e4 / PI), false),
+        "langley" => (joule.times(m.powi(-2)).scaled(41_840.0), false),
+        "Btu" | "BTU" => (joule.scaled(1_055.055_852_62), false),
+        "Ci" => (s_.powi(-1).scaled(3.7e10), false),
+        "R" => (coulomb.times(kg.powi(-1)).scaled(2.58e-4), false),
+        "Rd" => (s_.powi(-1).scaled(1e6), false),
+        "hp" => (watt.scaled(745.699_871_582_270_2), false),
+        "%" => (Unit::ONE.scaled(0.01), false),
+        "dB" => (db, false),
+        "MET" => (met, false),
+        "1" => (Unit::ONE, false),
+        _ => return None,
+    };
+    Some(unit)
+}
+
+/// Spelled-out unit names (matched case-insensitively, ignoring `_` and `-`), mapped to an
+/// expression. Names of `physics::` types are resolved separately.
+fn named(name: &str) -> Option<&'static str> {
+    Some(match name {
+        "meter" | "metre" => "m",
+        "gram" | "gramme" => "g",
+        "second" | "sec" => "s",
+        "minute" => "min",
+        "hour" => "h",
+        "day" => "d",
+        "week" => "wk",
+        "ampere" | "amp" => "A",
+        "liter" | "litre" => "L",
+        "tonne" => "t",
+        "degree" => "deg",
+        "foot" | "feet" => "ft",
+        "inch" | "inches" => "in",
+        "yard" => "yd",
+        "mile" => "mi",
+        "pound" => "lb",
+        "ounce" => "oz",
+        "knot" => "kn",
+        "au" => "au",
+        "ohm" => "Ω",
+        _ => return None,
+    })
+}
+
+/// Parses a unit expression: factors joined by `*` or `·`, each optionally raised to a
+/// power (`m^2`, `m²`, `s^-1`); every `/` divides by the factor group that follows it,
+/// which may be parenthesised (`W/(m·K)`).
+pub(crate) fn parse_unit(expr: &str) -> Result<Unit, String> {
+    let expr = expr.trim();
+    if expr.is_empty() {
+        return Err("missing unit".into());
+    }
+    // A single temperature unit keeps its offset; compound expressions are differences.
+    if let Some((unit, _)) = symbol(expr).filter(|(u, _)| u.offset != 0.0) {
+        return Ok(unit);
+    }
+
+    let mut groups = expr.split('/');
+    let mut unit = parse_product(groups.next().unwrap_or(""))?;
+    for group in groups {
+        let group = group.trim();
+        let group = group.strip_prefix('(').and_then(|g| g.strip_suffix(')')).unwrap_or(group);
+        unit = unit.times(parse_product(group)?.powi(-1));
+    }
+    Ok(unit)
+}
+
+fn parse_product(group: &str) -> Result<Unit, String> {
+    let group = group.trim();
+    if group.is_empty() {
+        return Err("empty unit factor".into());
+    }
+    group
+        .split(['*', '·', '⋅'])
+        .map(|factor| parse_factor(factor.trim()))
+        .try_fold(Unit::ONE, |acc, factor| Ok(acc.times(factor?)))
+}
+
+fn parse_factor(factor: &str) -> Result<Unit, String> {
+    let (name, exponent) = if let Some((name, exp)) = factor.split_once('^') {
+        (name, exp.trim().parse::<i32>().map_err(|_| format!("bad exponent in '{}'", factor))?)
+    } else if let Some(name) = factor.strip_suffix('²') {
+        (name, 2)
+    } else if let Some(name) = factor.strip_suffix('³') {
+        (name, 3)
+    } else {
+        (factor, 1)
+    };
+    if exponent == 0 || exponent.abs() > 9 {
+        return Err(format!("bad exponent in '{}'", factor));
+    }
+    Ok(lookup(name.trim()).ok_or_else(|| format!("unknown unit '{}'", name.trim()))?.powi(exponent))
+}
+
+fn lookup(name: &str) -> Option<Unit> {
+    if let Some((unit, _)) = symbol(name) {
+        return Some(unit);
+    }
+    for (prefix, factor) in PREFIXES {
+        if let Some((unit, true)) = name.strip_prefix(prefix).and_then(symbol) {
+            return Some(unit.scaled(factor));
+        }
+    }
+    lookup_name(name)
+}
+
+/// `kilometers`, `nauticalMile`, `meterPerSecond`: a spelled-out name, optionally plural
+/// and with a spelled-out prefix.
+fn lookup_name(name: &str) -> Option<Unit> {
+    let normalized = name.to_lowercase().replace(['_', '-'], "");
+    let singular = normalized.strip_suffix('s').unwrap_or(&normalized);
+
+    for candidate in [normalized.as_str(), singular] {
+        if let Some(expr) = named(candidate) {
+            return parse_unit(expr).ok();
+        }
+        if let Some(unit) = crate::types::physics::unit_of_name(candidate) {
+            return Some(unit);
+        }
+        for (prefix, factor) in NAME_PREFIXES {
+            if let Some(unit) = candidate.strip_prefix(prefix).filter(|rest| !rest.is_empty()).and_then(lookup_name) {
+                return Some(unit.scaled(factor));
+            }
+        }
+    }
+    None
+}
+
+/// Splits `30 km/h` into `30.0` and `"km/h"`; the unit is empty for a bare number.
+pub(crate) fn split_quantity(value: &str) -> Result<(f64, &str), String> {
+    let value = value.trim();
+    let numeric_len = value
+        .find(|c: char| !(c.is_ascii_digit() || matches!(c, '.' | 'e' | 'E' | '+' | '-')))
+        .unwrap_or(value.len());
+
+    // `10eV`: the longest prefix that is a number, so the exponent marker can start a unit.
+    (1..=numeric_len)
+        .rev()
+        .find_map(|end| value[..end].parse::<f64>().ok().map(|n| (n, value[end..].trim())))
+        .filter(|(n, _)| n.is_finite())
+        .ok_or_else(|| "expected a number".to_string())
+}
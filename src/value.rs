@@ -0,0 +1,161 @@
+//! Conversions from raw config values into Rust types, used by
+//! [`AAML::get_as`](crate::aaml::AAML::get_as).
+
+use std::fmt;
+use std::time::{Duration, SystemTime};
+use crate::error::AamlError;
+use crate::types::{color, data, math, time};
+
+/// A type that can be parsed from a config value.
+pub trait FromAaml: Sized {
+    fn from_aaml(value: &str) -> Result<Self, AamlError>;
+}
+
+fn invalid(expected: &str, value: &str) -> AamlError {
+    AamlError::InvalidValue(format!("Expected {}, got '{}'", expected, value))
+}
+
+macro_rules! from_str_impls {
+    ($($ty:ty => $name:literal),* $(,)?) => {
+        $(
+            impl FromAaml for $ty {
+                fn from_aaml(value: &str) -> Result<Self, AamlError> {
+                    value.trim().parse().map_err(|_| invalid($name, value))
+                }
+            }
+        )*
+    };
+}
+
+from_str_impls! {
+    i8 => "i8", i16 => "i16", i32 => "i32", i64 => "i64", i128 => "i128",
+    u8 => "u8", u16 => "u16", u32 => "u32", u64 => "u64", u128 => "u128",
+    f32 => "f32", f64 => "f64",
+}
+
+impl FromAaml for String {
+    fn from_aaml(value: &str) -> Result<Self, AamlError> {
+        Ok(value.to_string())
+    }
+}
+
+impl FromAaml for bool {
+    fn from_aaml(value: &str) -> Result<Self, AamlError> {
+        match value.trim().to_lowercase().as_str() {
+            "true" | "1" => Ok(true),
+            "false" | "0" => Ok(false),
+            _ => Err(invalid("bool (true/false/1/0)", value)),
+        }
+    }
+}
+
+impl FromAaml for char {
+    fn from_aaml(value: &str) -> Result<Self, AamlError> {
+        let mut chars = value.chars();
+        match (chars.next(), chars.next()) {
+            (Some(c), None) => Ok(c),
+            _ => Err(invalid("a single character", value)),
+        }
+    }
+}
+
+/// A vector or quaternion such as `1, 2, 3`, `(1, 2, 3)` or `[1, 2, 3]`.
+impl<const N: usize> FromAaml for [f64; N] {
+    fn from_aaml(value: &str) -> Result<Self, AamlError> {
+        let values = math::parse_matrix(value, 1, N).map_err(AamlError::InvalidValue)?;
+        Ok(std::array::from_fn(|i| values[i]))
+    }
+}
+
+/// A matrix in row-major order, written flat (`1, 0, 0, 1`) or row-wise (`[[1, 0], [0, 1]]`).
+impl<const R: usize, const C: usize> FromAaml for [[f64; C]; R] {
+    fn from_aaml(value: &str) -> Result<Self, AamlError> {
+        let values = math::parse_matrix(value, R, C).map_err(AamlError::InvalidValue)?;
+        Ok(std::array::from_fn(|r| std::array::from_fn(|c| values[r * C + c])))
+    }
+}
+
+/// A `data::bytes` value such as `10MiB`, as a number of bytes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct ByteSize(pub u64);
+
+/// A `data::bitrate` value such as `100 Mbps`, in bits per second.
+#[derive(Debug, Clone, Copy, PartialEq, PartialOrd)]
+pub struct Bitrate(pub f64);
+
+/// A `data::percentage` value such as `50%`, as a fraction (`0.5`).
+#[derive(Debug, Clone, Copy, PartialEq, PartialOrd)]
+pub struct Percentage(pub f64);
+
+impl FromAaml for ByteSize {
+    fn from_aaml(value: &str) -> Result<Self, AamlError> {
+        data::parse_bytes(value)
+            .map(ByteSize)
+            .map_err(|e| AamlError::InvalidValue(format!("Invalid bytes '{}': {}", value, e)))
+    }
+}
+
+impl FromAaml for Bitrate {
+    fn from_aaml(value: &str) -> Result<Self, AamlError> {
+        data::parse_bitrate(value)
+            .map(Bitrate)
+            .map_err(|e| AamlError::InvalidValue(format!("Invalid bitrate '{}': {}", value, e)))
+    }
+}
+
+impl FromAaml for Percentage {
+    fn from_aaml(value: &str) -> Result<Self, AamlError> {
+        data::parse_percentage(value)
+            .map(Percentage)
+            .map_err(|e| AamlError::InvalidValue(format!("Invalid percentage '{}': {}", value, e)))
+    }
+}
+
+/// A `color` value as 8-bit RGBA channels; colors without alpha are opaque (`a = 255`).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Color {
+    pub r: u8,
+    pub g: u8,
+    pub b: u8,
+    pub a: u8,
+}
+
+/// `#RGB`, `#RGBA`, `#RRGGBB`, `#RRGGBBAA`, CSS names (`rebeccapurple`, `transparent`) and
+/// `rgb()`, `rgba()`, `hsl()`, `hsla()` in either the comma or the space-separated syntax.
+impl FromAaml for Color {
+    fn from_aaml(value: &str) -> Result<Self, AamlError> {
+        color::parse_color(value).map_err(|e| AamlError::InvalidValue(format!("Invalid color '{}': {}", value, e)))
+    }
+}
+
+impl fmt::Display for Color {
+    /// `#rrggbb`, or `#rrggbbaa` when the color is not opaque.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "#{:02x}{:02x}{:02x}", self.r, self.g, self.b)?;
+        if self.a != 255 {
+            write!(f, "{:02x}", self.a)?;
+        }
+        Ok(())
+    }
+}
+
+/// Seconds (`90`), ISO 8601 durations (`PT1H30M`) and human durations (`1h30m`, `250ms`).
+/// Durations with years or months are rejected since their length depends on the calendar.
+impl FromAaml for Duration {
+    fn from_aaml(value: &str) -> Result<Self, AamlError> {
+        let invalid = |e: &str| AamlError::InvalidValue(format!("Invalid duration '{}': {}", value, e));
+        let parsed = time::parse_duration(value).map_err(|e| invalid(&e))?;
+        if parsed.calendar {
+            return Err(invalid("years and months have no fixed length"));
+        }
+        Ok(parsed.duration)
+    }
+}
+
+/// An ISO 8601 date or datetime; values without an offset are taken as UTC.
+impl FromAaml for SystemTime {
+    fn from_aaml(value: &str) -> Result<Self, AamlError> {
+        time::datetime_to_system_time(value)
+            .map_err(|e| AamlError::InvalidValue(format!("Invalid DateTime '{}': {}", value, e)))
+    }
+}
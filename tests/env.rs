@@ -0,0 +1,60 @@
+//! Tests that set environment variables. `set_var` races with any concurrent read of the
+//! environment, including the parser's own `$NAME` lookups, so they run as the only test
+//! of this binary.
+
+use aam_rs::aaml::AAML;
+use std::fs;
+
+fn set_var(name: &str, value: &str) {
+    // SAFETY: this binary runs a single test, so no other thread reads the environment.
+    unsafe { std::env::set_var(name, value) };
+}
+
+#[test]
+fn environment_variables() {
+    conditions_read_the_environment();
+    load_cached_checks_env();
+    compiled_records_env_of_namespaced_imports();
+}
+
+fn conditions_read_the_environment() {
+    set_var("AAML_TEST_CONDITIONAL_REGION", "eu");
+    let content = "@if $AAML_TEST_CONDITIONAL_REGION == \"eu\" || $AAML_TEST_UNSET_XYZ\nregion = eu\n@endif";
+    let parser = AAML::parse(content).unwrap();
+    assert_eq!(parser.find_obj("region").unwrap().as_str(), "eu");
+}
+
+fn load_cached_checks_env() {
+    let root = "test_env_cached.aam";
+    let cache = "test_env_cached.aamc";
+    fs::write(root, "@if $AAML_TEST_COMPILED_REGION == \"eu\"\nregion = eu\n@endif").unwrap();
+
+    let plain = AAML::load_cached(root, cache);
+    set_var("AAML_TEST_COMPILED_REGION", "eu");
+    let stale = fs::read(cache).map(|bytes| AAML::is_compiled_fresh(&bytes));
+    let with_env = AAML::load_cached(root, cache);
+
+    let _ = fs::remove_file(root);
+    let _ = fs::remove_file(cache);
+
+    assert_eq!(plain.unwrap().find_obj_ref("region"), None);
+    assert!(matches!(stale, Ok(Ok(false))));
+    assert_eq!(with_env.unwrap().find_obj_ref("region"), Some("eu"));
+}
+
+fn compiled_records_env_of_namespaced_imports() {
+    let root = "test_env_ns_root.aam";
+    let sub = "test_env_ns_sub.aam";
+    fs::write(sub, "@if $AAML_TEST_COMPILED_NS_ENV\ndebug = true\n@endif").unwrap();
+    fs::write(root, format!("@import {sub} as db")).unwrap();
+
+    let blob = AAML::compile(root);
+    let fresh_before = blob.as_ref().map(|b| AAML::is_compiled_fresh(b));
+    set_var("AAML_TEST_COMPILED_NS_ENV", "1");
+    let fresh_after = blob.as_ref().map(|b| AAML::is_compiled_fresh(b));
+    let _ = fs::remove_file(root);
+    let _ = fs::remove_file(sub);
+
+    assert!(matches!(fresh_before, Ok(Ok(true))));
+    assert!(matches!(fresh_after, Ok(Ok(false))));
+}